@@ -2,11 +2,11 @@
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use fastrand::Rng;
-use zeno::{Command, Mask, PathBuilder, Scratch, Style};
+use zeno::{Cap, Command, Format, Join, Mask, PathBuilder, Scratch, Stroke, Style};
 
 fn drawing(c: &mut Criterion) {
     // Set up buffers for rendering.
-    let mut buffer = Box::new([0u8; 1024 * 1024]);
+    let mut buffer = vec![0u8; 1024 * 1024 * 4].into_boxed_slice();
     let mut scratch = Scratch::new();
     let mut rng = Rng::with_seed(0x12345678);
 
@@ -20,7 +20,7 @@ fn drawing(c: &mut Criterion) {
         b.iter(|| {
             Mask::with_scratch(&path, &mut scratch)
                 .style(Style::Fill(zeno::Fill::EvenOdd))
-                .render_into(&mut *buffer, None);
+                .render_into(&mut buffer, None);
             black_box((&mut scratch, &mut buffer));
         });
     });
@@ -48,7 +48,7 @@ fn drawing(c: &mut Criterion) {
         b.iter(|| {
             Mask::with_scratch(&path, &mut scratch)
                 .style(Style::Fill(zeno::Fill::EvenOdd))
-                .render_into(&mut *buffer, None);
+                .render_into(&mut buffer, None);
             black_box((&mut scratch, &mut buffer));
         })
     });
@@ -63,7 +63,53 @@ fn drawing(c: &mut Criterion) {
         b.iter(|| {
             Mask::with_scratch(&path, &mut scratch)
                 .style(Style::Fill(zeno::Fill::EvenOdd))
-                .render_into(&mut *buffer, None);
+                .render_into(&mut buffer, None);
+            black_box((&mut scratch, &mut buffer));
+        });
+    });
+
+    // A dashed stroke rendered as a single channel, as a subpixel mask and
+    // as three offset single channel masks, which is the cost of rendering
+    // each channel separately.
+    let stroke_path = {
+        let mut path = Vec::<Command>::new();
+        path.add_circle((500.0, 500.0), 450.0);
+        path.add_round_rect((100.0, 100.0), 800.0, 800.0, 40.0, 40.0);
+        path
+    };
+    let mut stroke = Stroke::new(6.0);
+    stroke
+        .join(Join::Round)
+        .cap(Cap::Round)
+        .dash(&[12.0, 8.0, 3.0, 8.0], 0.0);
+
+    c.bench_function("stroke_alpha", |b| {
+        b.iter(|| {
+            Mask::with_scratch(&stroke_path, &mut scratch)
+                .style(stroke)
+                .render_into(&mut buffer, Some(1024));
+            black_box((&mut scratch, &mut buffer));
+        });
+    });
+
+    c.bench_function("stroke_subpixel", |b| {
+        b.iter(|| {
+            Mask::with_scratch(&stroke_path, &mut scratch)
+                .style(stroke)
+                .format(Format::Subpixel)
+                .render_into(&mut buffer, Some(1024 * 4));
+            black_box((&mut scratch, &mut buffer));
+        });
+    });
+
+    c.bench_function("stroke_subpixel_separate", |b| {
+        b.iter(|| {
+            for x in [-0.3, 0.0, 0.3] {
+                Mask::with_scratch(&stroke_path, &mut scratch)
+                    .style(stroke)
+                    .render_offset((x, 0.0))
+                    .render_into(&mut buffer, Some(1024));
+            }
             black_box((&mut scratch, &mut buffer));
         });
     });
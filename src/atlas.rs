@@ -0,0 +1,486 @@
+//! Texture atlas packing.
+
+use super::geometry::Placement;
+use super::mask::{Format, Mask};
+use super::path_data::PathData;
+
+use crate::lib::Vec;
+
+/// Handle to an entry in an atlas.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct AtlasId {
+    index: u32,
+    generation: u32,
+}
+
+/// Location of a mask that has been rendered into an atlas.
+#[derive(Copy, Clone, Debug)]
+pub struct AtlasEntry {
+    /// Handle to the entry.
+    pub id: AtlasId,
+    /// Horizontal position of the mask in the atlas in pixels.
+    pub x: u32,
+    /// Vertical position of the mask in the atlas in pixels.
+    pub y: u32,
+    /// Placement of the mask. The width and height describe the extent of
+    /// the mask in the atlas.
+    pub placement: Placement,
+}
+
+/// Image that packs many masks into a single shared buffer.
+///
+/// Space is allocated from horizontal shelves, each holding masks of similar
+/// height. Entries are separated by a configurable amount of padding that is
+/// cleared to zero to prevent neighboring masks from bleeding into each other
+/// when sampled with filtering. Evicted entries return their space to the
+/// shelf they were allocated from, and the atlas may optionally grow up to a
+/// maximum size when it runs out of space. Growing never moves existing
+/// entries.
+///
+/// All masks in an atlas share the same format, which may be any format with
+/// whole bytes per pixel such as [`Format::Alpha`] or one of the subpixel
+/// formats.
+///
+/// # Example
+///
+/// ```rust
+/// use zeno::{Atlas, Format, Mask};
+///
+/// let mut atlas = Atlas::new(Format::Alpha, 64, 64);
+/// atlas.padding(1);
+/// let entry = atlas.insert(&mut Mask::new("M 0,0 L 10,0 L 10,10 Z")).unwrap();
+/// assert_eq!((entry.x, entry.y), (1, 1));
+/// assert_eq!((entry.placement.width, entry.placement.height), (10, 10));
+/// let pixel = atlas.data()[(entry.y as usize + 2) * atlas.pitch() + entry.x as usize + 8];
+/// assert_eq!(pixel, 255);
+/// atlas.evict(entry.id);
+///
+/// // Empty masks occupy no space, regardless of padding
+/// atlas.padding(100);
+/// let empty = atlas.insert(&mut Mask::new("")).unwrap();
+/// assert_eq!((empty.placement.width, empty.placement.height), (0, 0));
+/// ```
+#[derive(Clone)]
+pub struct Atlas {
+    format: Format,
+    width: u32,
+    height: u32,
+    max_width: u32,
+    max_height: u32,
+    padding: u32,
+    data: Vec<u8>,
+    shelves: Vec<Shelf>,
+    slots: Vec<Slot>,
+    free_slots: Vec<u32>,
+}
+
+impl Atlas {
+    /// Creates a new atlas with the specified format and initial size.
+    ///
+    /// Panics if the format is bit-packed such as [`Format::Mono`] since
+    /// entries may not begin on a byte boundary.
+    pub fn new(format: Format, width: u32, height: u32) -> Self {
+        assert!(
+            !matches!(format, Format::Mono { .. }),
+            "bit-packed formats are not supported in an atlas"
+        );
+        let data = alloc::vec![0; format.buffer_size(width, height)];
+        Self {
+            format,
+            width,
+            height,
+            max_width: width,
+            max_height: height,
+            padding: 0,
+            data,
+            shelves: Vec::new(),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+        }
+    }
+
+    /// Sets the number of pixels of padding that surround each entry. The
+    /// default is zero. Changes apply to subsequently inserted entries.
+    pub fn padding(&mut self, padding: u32) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the maximum size to which the atlas will automatically grow when
+    /// an entry does not fit. The default is the initial size, which
+    /// disables growth.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zeno::{Atlas, Format, Mask};
+    ///
+    /// let mut atlas = Atlas::new(Format::Alpha, 64, 64);
+    /// atlas.max_size(1024, 1024);
+    /// // Only the width is too small for this entry
+    /// atlas.insert(&mut Mask::new("M 0,0 100,0 100,10 0,10 Z")).unwrap();
+    /// assert_eq!((atlas.width(), atlas.height()), (128, 64));
+    /// ```
+    pub fn max_size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.max_width = width.max(self.width);
+        self.max_height = height.max(self.height);
+        self
+    }
+
+    /// Returns the format of the atlas.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the current width of the atlas in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the current height of the atlas in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the number of bytes between subsequent rows of the atlas.
+    pub fn pitch(&self) -> usize {
+        self.format.pitch(self.width)
+    }
+
+    /// Returns the image data of the atlas.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Renders the mask into the atlas and returns the resulting entry, or
+    /// `None` if there is no space available for it.
+    ///
+    /// The mask is rendered with the format of the atlas, and its size and
+    /// placement are determined in the same manner as [`Mask::render`]. The
+    /// settings of the mask builder are left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zeno::{Atlas, Format, Mask, Transform};
+    ///
+    /// let mut atlas = Atlas::new(Format::Subpixel, 64, 64);
+    /// let mut mask = Mask::new("M 0,0 10,0 10,10 0,10 Z");
+    /// atlas.insert(&mut mask).unwrap();
+    /// // The mask keeps its own format and computes its size from the bounds
+    /// let (data, placement) = mask.transform(Some(Transform::scale(2., 2.))).render();
+    /// assert_eq!((placement.width, placement.height), (20, 20));
+    /// assert_eq!(data.len(), 400);
+    /// ```
+    pub fn insert<D: PathData>(&mut self, mask: &mut Mask<D>) -> Option<AtlasEntry> {
+        let (alloc, x, y, placement) = mask.with_format(self.format, |mask| {
+            let (_, placement) = mask.placement();
+            let alloc = self.allocate(placement.width, placement.height)?;
+            if alloc.shelf.is_none() {
+                // Empty masks occupy no space in the atlas.
+                return Some((alloc, 0, 0, placement));
+            }
+            let (x, y) = (alloc.x + alloc.padding, alloc.y + alloc.padding);
+            let bpp = self.format.pitch(1);
+            let pitch = self.pitch();
+            let offset = y as usize * pitch + x as usize * bpp;
+            let placement = mask.render_into(&mut self.data[offset..], Some(pitch));
+            Some((alloc, x, y, placement))
+        })?;
+        let entry = Entry {
+            alloc,
+            x,
+            y,
+            placement,
+        };
+        let index = match self.free_slots.pop() {
+            Some(index) => {
+                self.slots[index as usize].entry = Some(entry);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    entry: Some(entry),
+                });
+                self.slots.len() as u32 - 1
+            }
+        };
+        let id = AtlasId {
+            index,
+            generation: self.slots[index as usize].generation,
+        };
+        Some(entry.to_atlas_entry(id))
+    }
+
+    /// Returns the entry with the specified handle, or `None` if it has been
+    /// evicted.
+    pub fn get(&self, id: AtlasId) -> Option<AtlasEntry> {
+        let slot = self.slots.get(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        Some(slot.entry?.to_atlas_entry(id))
+    }
+
+    /// Removes the entry with the specified handle, making its space
+    /// available for subsequent insertions. Returns `false` if the entry has
+    /// already been evicted.
+    pub fn evict(&mut self, id: AtlasId) -> bool {
+        let Some(slot) = self.slots.get_mut(id.index as usize) else {
+            return false;
+        };
+        if slot.generation != id.generation {
+            return false;
+        }
+        let Some(Entry { alloc, .. }) = slot.entry.take() else {
+            return false;
+        };
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(id.index);
+        if let Some(shelf) = alloc.shelf {
+            self.shelves[shelf].release(alloc.x, alloc.width);
+            while self.shelves.last().is_some_and(|s| s.is_empty(self.width)) {
+                self.shelves.pop();
+            }
+        }
+        true
+    }
+
+    /// Removes all entries and clears the image data.
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots {
+            if slot.entry.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+            }
+        }
+        self.free_slots.clear();
+        self.free_slots.extend(0..self.slots.len() as u32);
+        self.shelves.clear();
+        self.data.fill(0);
+    }
+
+    /// Resizes the atlas to at least the specified dimensions, preserving
+    /// existing entries. The atlas never shrinks.
+    pub fn grow(&mut self, width: u32, height: u32) {
+        let width = width.max(self.width);
+        let height = height.max(self.height);
+        if width == self.width && height == self.height {
+            return;
+        }
+        if width != self.width {
+            let old_pitch = self.pitch();
+            let new_pitch = self.format.pitch(width);
+            let mut data = alloc::vec![0; self.format.buffer_size(width, height)];
+            for (src, dst) in self
+                .data
+                .chunks_exact(old_pitch.max(1))
+                .zip(data.chunks_exact_mut(new_pitch))
+            {
+                dst[..old_pitch].copy_from_slice(src);
+            }
+            self.data = data;
+            for shelf in &mut self.shelves {
+                shelf.release(self.width, width - self.width);
+            }
+        } else {
+            self.data.resize(self.format.buffer_size(width, height), 0);
+        }
+        self.width = width;
+        self.height = height;
+        self.max_width = self.max_width.max(width);
+        self.max_height = self.max_height.max(height);
+    }
+
+    /// Allocates and clears a padded region for a mask of the specified
+    /// size. Empty masks receive an unplaced allocation with no area.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<Alloc> {
+        let mut alloc = Alloc {
+            shelf: None,
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            padding: 0,
+        };
+        if width == 0 || height == 0 {
+            return Some(alloc);
+        }
+        let padding = self.padding;
+        alloc.width = width + padding * 2;
+        alloc.height = height + padding * 2;
+        alloc.padding = padding;
+        let (shelf, x) = loop {
+            if let Some(found) = self.find(alloc.width, alloc.height) {
+                break found;
+            }
+            if !self.grow_step(alloc.width, alloc.height) {
+                return None;
+            }
+        };
+        alloc.shelf = Some(shelf);
+        alloc.x = x;
+        alloc.y = self.shelves[shelf].y;
+        self.clear_rect(&alloc);
+        Some(alloc)
+    }
+
+    /// Finds space for a padded region, opening a new shelf if necessary.
+    /// Returns the shelf index and horizontal position.
+    fn find(&mut self, width: u32, height: u32) -> Option<(usize, u32)> {
+        if width > self.width {
+            return None;
+        }
+        let mut best: Option<(usize, u32)> = None;
+        for (index, shelf) in self.shelves.iter().enumerate() {
+            if shelf.height < height || !shelf.fits(width) {
+                continue;
+            }
+            let waste = shelf.height - height;
+            if best.is_none_or(|(_, best_waste)| waste < best_waste) {
+                best = Some((index, waste));
+            }
+        }
+        let bottom = self.shelves.last().map_or(0, |s| s.y + s.height);
+        let can_open = height <= self.height - bottom;
+        let index = match best {
+            // Avoid placing short entries on tall shelves when a better
+            // fitting shelf can be opened.
+            Some((index, waste)) if !can_open || waste <= height / 2 => index,
+            _ if can_open => {
+                self.shelves.push(Shelf::new(bottom, height, self.width));
+                self.shelves.len() - 1
+            }
+            _ => return None,
+        };
+        let x = self.shelves[index].take(width)?;
+        Some((index, x))
+    }
+
+    /// Grows the atlas to make room for a padded region of the specified
+    /// size. The width is doubled if it is too small for the region.
+    /// Otherwise, the height is doubled, or the width once the maximum height
+    /// has been reached. Returns `false` if the region cannot fit within the
+    /// maximum size.
+    fn grow_step(&mut self, width: u32, height: u32) -> bool {
+        if width > self.max_width || height > self.max_height {
+            false
+        } else if width > self.width {
+            let width = (self.width.max(1) * 2).min(self.max_width);
+            self.grow(width, self.height);
+            true
+        } else if self.height < self.max_height {
+            let height = (self.height.max(1) * 2).min(self.max_height);
+            self.grow(self.width, height);
+            true
+        } else if self.width < self.max_width {
+            let width = (self.width.max(1) * 2).min(self.max_width);
+            self.grow(width, self.height);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn clear_rect(&mut self, alloc: &Alloc) {
+        let bpp = self.format.pitch(1);
+        let pitch = self.pitch();
+        let start = alloc.x as usize * bpp;
+        let end = start + alloc.width as usize * bpp;
+        for row in alloc.y..alloc.y + alloc.height {
+            let row = row as usize * pitch;
+            self.data[row + start..row + end].fill(0);
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Slot {
+    generation: u32,
+    entry: Option<Entry>,
+}
+
+#[derive(Copy, Clone)]
+struct Entry {
+    alloc: Alloc,
+    x: u32,
+    y: u32,
+    placement: Placement,
+}
+
+impl Entry {
+    fn to_atlas_entry(self, id: AtlasId) -> AtlasEntry {
+        AtlasEntry {
+            id,
+            x: self.x,
+            y: self.y,
+            placement: self.placement,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Alloc {
+    shelf: Option<usize>,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    padding: u32,
+}
+
+/// Horizontal strip of the atlas with a list of free spans.
+#[derive(Clone)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Free spans as (start, end) pairs sorted by start.
+    free: Vec<(u32, u32)>,
+}
+
+impl Shelf {
+    fn new(y: u32, height: u32, width: u32) -> Self {
+        Self {
+            y,
+            height,
+            free: alloc::vec![(0, width)],
+        }
+    }
+
+    fn fits(&self, width: u32) -> bool {
+        self.free.iter().any(|span| span.1 - span.0 >= width)
+    }
+
+    fn is_empty(&self, width: u32) -> bool {
+        self.free.len() == 1 && self.free[0] == (0, width)
+    }
+
+    /// Takes space from the first free span that fits.
+    fn take(&mut self, width: u32) -> Option<u32> {
+        let index = self.free.iter().position(|span| span.1 - span.0 >= width)?;
+        let span = &mut self.free[index];
+        let x = span.0;
+        span.0 += width;
+        if span.0 == span.1 {
+            self.free.remove(index);
+        }
+        Some(x)
+    }
+
+    /// Returns space to the shelf, merging with adjacent free spans.
+    fn release(&mut self, x: u32, width: u32) {
+        let end = x + width;
+        let index = self.free.partition_point(|span| span.0 < x);
+        let merge_prev = index > 0 && self.free[index - 1].1 == x;
+        let merge_next = index < self.free.len() && self.free[index].0 == end;
+        match (merge_prev, merge_next) {
+            (true, true) => {
+                self.free[index - 1].1 = self.free[index].1;
+                self.free.remove(index);
+            }
+            (true, false) => self.free[index - 1].1 = end,
+            (false, true) => self.free[index].0 = x,
+            (false, false) => self.free.insert(index, (x, end)),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Batch rendering.
+
+use super::command::Command;
+use super::geometry::{Origin, Placement, Transform};
+use super::mask::{CoverageCurve, Format, LcdFilter, Mask};
+use super::path_data::PathData;
+use super::scratch::Scratch;
+use super::style::Style;
+
+use crate::lib::Vec;
+
+/// Builder for rendering many paths into separate masks with shared
+/// settings.
+///
+/// Each job is a tuple of path data, style and optional transform. The style
+/// and transform are applied to each path exactly once and the resulting
+/// masks are returned in the same order as the jobs along with their
+/// placements. Scratch memory is reused across all jobs rendered by a
+/// worker.
+///
+/// # Example
+///
+/// ```rust
+/// use zeno::{Batch, Fill, Format, Stroke, Style, Transform};
+///
+/// let jobs = [
+///     ("M 8,56 32,8 56,56 Z", Style::Fill(Fill::NonZero), None),
+///     ("M 0,0 L 10,10", Stroke::new(2.0).into(), Some(Transform::scale(2.0, 2.0))),
+/// ];
+/// let masks = Batch::new().format(Format::Alpha).render(jobs);
+///
+/// assert_eq!(masks.len(), 2);
+/// let (mask, placement) = &masks[0];
+/// assert_eq!(mask.len(), (placement.width * placement.height) as usize);
+/// ```
+#[derive(Copy, Clone)]
+pub struct Batch<'c> {
+    format: Format,
+    origin: Origin,
+    curve: CoverageCurve<'c>,
+    lcd_filter: LcdFilter,
+    #[cfg(feature = "std")]
+    threads: usize,
+}
+
+impl Default for Batch<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'c> Batch<'c> {
+    /// Creates a new batch builder.
+    pub fn new() -> Self {
+        Self {
+            format: Format::default(),
+            origin: Origin::default(),
+            curve: CoverageCurve::Linear,
+            lcd_filter: LcdFilter::None,
+            #[cfg(feature = "std")]
+            threads: 1,
+        }
+    }
+
+    /// Sets the desired format of the masks. The default value is an 8-bit
+    /// alpha format.
+    pub fn format(&mut self, format: Format) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the origin that defines the coordinate system for the masks.
+    pub fn origin(&mut self, origin: Origin) -> &mut Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Sets the transfer curve that is applied to coverage values while
+    /// rendering. The default is a linear curve.
+    pub fn coverage_curve(&mut self, curve: CoverageCurve<'c>) -> &mut Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Sets the filter that is applied across neighboring subpixels for
+    /// subpixel formats. The default is no filtering.
+    pub fn lcd_filter(&mut self, filter: LcdFilter) -> &mut Self {
+        self.lcd_filter = filter;
+        self
+    }
+
+    /// Sets the number of worker threads used for rendering. Jobs are divided
+    /// into contiguous groups, each rendered on a separate thread with its
+    /// own scratch memory. The default is one, which renders all jobs on the
+    /// calling thread.
+    #[cfg(feature = "std")]
+    pub fn threads(&mut self, count: usize) -> &mut Self {
+        self.threads = count;
+        self
+    }
+
+    /// Renders the paths described by the jobs and returns the resulting
+    /// masks and placements in the order of the jobs.
+    pub fn render<'a, D, S>(
+        &self,
+        jobs: impl IntoIterator<Item = (D, S, Option<Transform>)>,
+    ) -> Vec<(Vec<u8>, Placement)>
+    where
+        D: PathData + Send,
+        S: Into<Style<'a>>,
+    {
+        let jobs = jobs
+            .into_iter()
+            .map(|(data, style, transform)| (data, style.into(), transform));
+        #[cfg(feature = "std")]
+        if self.threads > 1 {
+            let jobs: Vec<_> = jobs.collect();
+            let count = jobs.len().div_ceil(self.threads).max(1);
+            let mut groups = Vec::new();
+            let mut jobs = jobs.into_iter();
+            loop {
+                let group: Vec<_> = jobs.by_ref().take(count).collect();
+                if group.is_empty() {
+                    break;
+                }
+                groups.push(group);
+            }
+            return std::thread::scope(|scope| {
+                let workers: Vec<_> = groups
+                    .into_iter()
+                    .map(|group| scope.spawn(move || self.render_group(group)))
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|worker| {
+                        worker
+                            .join()
+                            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                    })
+                    .collect()
+            });
+        }
+        self.render_group(jobs)
+    }
+
+    /// Renders a group of jobs on the current thread with a single scratch
+    /// memory context.
+    fn render_group<'a, D>(
+        &self,
+        jobs: impl IntoIterator<Item = (D, Style<'a>, Option<Transform>)>,
+    ) -> Vec<(Vec<u8>, Placement)>
+    where
+        D: PathData,
+    {
+        let mut scratch = Scratch::new();
+        let mut commands: Vec<Command> = Vec::new();
+        let mut masks = Vec::new();
+        for (data, style, transform) in jobs {
+            commands.clear();
+            let fill = scratch.apply(data, style, transform, &mut commands);
+            masks.push(
+                Mask::with_scratch(&commands[..], &mut scratch)
+                    .style(fill)
+                    .format(self.format)
+                    .origin(self.origin)
+                    .coverage_curve(self.curve)
+                    .lcd_filter(self.lcd_filter)
+                    .render(),
+            );
+        }
+        masks
+    }
+}
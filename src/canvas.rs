@@ -0,0 +1,213 @@
+//! Solid color painting.
+
+use super::error::Error;
+use super::geometry::{Placement, Transform};
+use super::mask::{mul_u8, Mask};
+use super::path_data::PathData;
+use super::scratch::Scratch;
+use super::style::{Fill, Stroke, Style};
+
+/// Pixel format of the target buffer of a canvas.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum PixelFormat {
+    /// 32-bit premultiplied pixels with channels in RGBA order.
+    #[default]
+    Rgba8,
+    /// 32-bit premultiplied pixels with channels in BGRA order.
+    Bgra8,
+    /// 8-bit grayscale pixels without alpha.
+    Gray8,
+}
+
+impl PixelFormat {
+    /// Returns the number of bytes per pixel.
+    fn size(self) -> usize {
+        match self {
+            Self::Rgba8 | Self::Bgra8 => 4,
+            Self::Gray8 => 1,
+        }
+    }
+}
+
+/// Porter-Duff operator for compositing a color with the target buffer.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum BlendMode {
+    /// The color is composited over the existing contents.
+    #[default]
+    SrcOver,
+    /// The color replaces the existing contents.
+    Src,
+    /// The existing contents are cleared.
+    Clear,
+}
+
+/// Painting context for filling and stroking paths with solid colors
+/// directly into a pixel buffer.
+///
+/// Paths are rasterized as spans of coverage which are blended with the
+/// target buffer according to the blend mode, so no intermediate mask is
+/// allocated. Colors are premultiplied RGBA and are converted to luminance
+/// for grayscale targets. Geometry outside of the buffer is discarded.
+///
+/// # Example
+///
+/// ```rust
+/// use zeno::{Canvas, Fill, PixelFormat, Stroke};
+///
+/// let mut pixels = [0u8; 16 * 16 * 4];
+/// let mut canvas = Canvas::new(&mut pixels, 16, 16, 16 * 4, PixelFormat::Rgba8).unwrap();
+///
+/// // Opaque red square with a half transparent black outline
+/// canvas
+///     .fill("M 4,4 12,4 12,12 4,12 Z", Fill::NonZero, [255, 0, 0, 255])
+///     .stroke("M 4,4 12,4 12,12 4,12 Z", &Stroke::new(2.0), [0, 0, 0, 128]);
+///
+/// assert_eq!(pixels[(8 * 16 + 8) * 4..][..4], [255, 0, 0, 255]);
+/// assert_eq!(pixels[(4 * 16 + 8) * 4..][..4], [127, 0, 0, 255]);
+/// assert_eq!(pixels[..4], [0, 0, 0, 0]);
+/// ```
+pub struct Canvas<'a> {
+    data: &'a mut [u8],
+    width: u32,
+    height: u32,
+    pitch: usize,
+    format: PixelFormat,
+    blend_mode: BlendMode,
+    transform: Option<Transform>,
+    scratch: Scratch,
+}
+
+impl<'a> Canvas<'a> {
+    /// Creates a new canvas for a buffer with the specified size, pitch and
+    /// pixel format. The pitch is the number of bytes between subsequent
+    /// rows of the buffer. Returns an error if the buffer is too small.
+    pub fn new(
+        data: &'a mut [u8],
+        width: u32,
+        height: u32,
+        pitch: usize,
+        format: PixelFormat,
+    ) -> Result<Self, Error> {
+        let row = (width as usize)
+            .checked_mul(format.size())
+            .ok_or(Error::DimensionsTooLarge)?;
+        if height != 0 {
+            let size = (height as usize - 1)
+                .checked_mul(pitch)
+                .and_then(|size| size.checked_add(row));
+            if pitch < row || size.is_none_or(|size| size > data.len()) {
+                return Err(Error::BufferTooSmall);
+            }
+        }
+        Ok(Self {
+            data,
+            width,
+            height,
+            pitch,
+            format,
+            blend_mode: BlendMode::SrcOver,
+            transform: None,
+            scratch: Scratch::new(),
+        })
+    }
+
+    /// Returns the width of the canvas in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the canvas in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the pixel format of the canvas.
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Sets the blend mode for subsequent painting. The default is
+    /// [`BlendMode::SrcOver`].
+    pub fn blend_mode(&mut self, mode: BlendMode) -> &mut Self {
+        self.blend_mode = mode;
+        self
+    }
+
+    /// Sets the transformation matrix for subsequent painting.
+    pub fn transform(&mut self, transform: Option<Transform>) -> &mut Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Fills the path with the specified premultiplied color.
+    pub fn fill(&mut self, data: impl PathData, fill: Fill, color: [u8; 4]) -> &mut Self {
+        self.paint(data, fill, color)
+    }
+
+    /// Strokes the path with the specified premultiplied color.
+    pub fn stroke(&mut self, data: impl PathData, stroke: &Stroke, color: [u8; 4]) -> &mut Self {
+        self.paint(data, stroke, color)
+    }
+
+    /// Paints the path with the specified style and premultiplied color.
+    pub fn paint<'s>(
+        &mut self,
+        data: impl PathData,
+        style: impl Into<Style<'s>>,
+        color: [u8; 4],
+    ) -> &mut Self {
+        if self.width == 0 || self.height == 0 {
+            return self;
+        }
+        let Self {
+            data: target,
+            pitch,
+            format,
+            blend_mode,
+            scratch,
+            ..
+        } = self;
+        let [r, g, b, a] = color;
+        let (color, channels) = match format {
+            PixelFormat::Rgba8 => ([r, g, b, a], 4),
+            PixelFormat::Bgra8 => ([b, g, r, a], 4),
+            PixelFormat::Gray8 => {
+                let gray = (r as u32 * 54 + g as u32 * 183 + b as u32 * 19 + 128) >> 8;
+                ([gray as u8, 0, 0, a], 1)
+            }
+        };
+        let clip = Placement {
+            left: 0,
+            top: 0,
+            width: self.width,
+            height: self.height,
+        };
+        Mask::with_scratch(data, scratch)
+            .style(style)
+            .transform(self.transform)
+            .clip(clip)
+            .render_spans(|y, x, len, coverage| {
+                let start = y as usize * *pitch + x as usize * channels;
+                let span = &mut target[start..start + len as usize * channels];
+                for pixel in span.chunks_exact_mut(channels) {
+                    for (i, dst) in pixel.iter_mut().enumerate() {
+                        *dst = blend(*blend_mode, *dst, color[i], color[3], coverage);
+                    }
+                }
+            });
+        self
+    }
+}
+
+/// Blends a premultiplied source channel with alpha into a destination
+/// channel with the specified coverage.
+#[inline(always)]
+fn blend(mode: BlendMode, dst: u8, src: u8, alpha: u8, coverage: u8) -> u8 {
+    match mode {
+        BlendMode::SrcOver => {
+            mul_u8(src, coverage).saturating_add(mul_u8(dst, 255 - mul_u8(alpha, coverage)))
+        }
+        BlendMode::Src => mul_u8(src, coverage).saturating_add(mul_u8(dst, 255 - coverage)),
+        BlendMode::Clear => mul_u8(dst, 255 - coverage),
+    }
+}
@@ -0,0 +1,38 @@
+//! Error type.
+
+use core::fmt;
+
+/// Errors that can occur when rendering a mask.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The target buffer is too small to hold the mask, or the pitch is
+    /// smaller than a row of the mask.
+    BufferTooSmall,
+    /// The dimensions of the mask exceed the supported range, the size of
+    /// the mask in bytes is not representable, or the geometry lies too far
+    /// from the mask for the selected rasterizer.
+    DimensionsTooLarge,
+    /// The path data, transform or offsets contain infinite or NaN values.
+    NonFiniteGeometry,
+    /// The stroke has a negative or non-finite width or miter limit, an
+    /// invalid dash pattern, or a dash pattern that would repeat an excessive
+    /// number of times along the path.
+    InvalidStroke,
+    /// Memory for the mask could not be allocated.
+    AllocationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::BufferTooSmall => "buffer too small",
+            Self::DimensionsTooLarge => "dimensions too large",
+            Self::NonFiniteGeometry => "non-finite geometry",
+            Self::InvalidStroke => "invalid stroke",
+            Self::AllocationFailed => "allocation failed",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -440,20 +440,15 @@ impl Transform {
 }
 
 /// The origin of the coordinate system for rendering.
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
 pub enum Origin {
     /// Origin (0, 0) at the top left of the image.
+    #[default]
     TopLeft,
     /// Origin (0, 0) at the bottom left of the image.
     BottomLeft,
 }
 
-impl Default for Origin {
-    fn default() -> Self {
-        Self::TopLeft
-    }
-}
-
 /// Describes the offset and dimensions of a rendered mask.
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Placement {
@@ -552,6 +547,7 @@ impl Bounds {
 
 pub(super) struct BoundsBuilder {
     pub count: usize,
+    pub loose: bool,
     #[allow(dead_code)]
     pub start: Point,
     pub current: Point,
@@ -563,6 +559,7 @@ impl BoundsBuilder {
     pub fn new() -> Self {
         Self {
             count: 0,
+            loose: false,
             start: Point::ZERO,
             current: Point::ZERO,
             min: Point::new(f32::MAX, f32::MAX),
@@ -589,6 +586,92 @@ impl BoundsBuilder {
         self
     }
 
+    /// Creates a builder that includes the control points of curves rather
+    /// than their extrema.
+    #[cfg(feature = "eval")]
+    pub fn loose() -> Self {
+        Self {
+            loose: true,
+            ..Self::new()
+        }
+    }
+
+    /// Adds a quadratic curve, including either the control point or the
+    /// extrema depending on the mode of the builder.
+    pub fn add_quad(&mut self, p0: Point, c: Point, p1: Point) -> &mut Self {
+        if self.loose {
+            return self.add(c).add(p1);
+        }
+        self.add(p1);
+        if self.contains(c) {
+            return self;
+        }
+        let eval = |t: f32| {
+            let s = 1. - t;
+            p0 * (s * s) + c * (2. * s * t) + p1 * (t * t)
+        };
+        for (a, b, c) in [(p0.x, c.x, p1.x), (p0.y, c.y, p1.y)] {
+            // Root of the derivative: 2(1-t)(b-a) + 2t(c-b)
+            let d = a - 2. * b + c;
+            if d != 0. {
+                let t = (a - b) / d;
+                if t > 0. && t < 1. {
+                    self.add(eval(t));
+                }
+            }
+        }
+        self
+    }
+
+    /// Adds a cubic curve, including either the control points or the
+    /// extrema depending on the mode of the builder.
+    pub fn add_cubic(&mut self, p0: Point, c1: Point, c2: Point, p1: Point) -> &mut Self {
+        if self.loose {
+            return self.add(c1).add(c2).add(p1);
+        }
+        self.add(p1);
+        if self.contains(c1) && self.contains(c2) {
+            return self;
+        }
+        let eval = |t: f32| {
+            let s = 1. - t;
+            p0 * (s * s * s) + c1 * (3. * s * s * t) + c2 * (3. * s * t * t) + p1 * (t * t * t)
+        };
+        for (p0, c1, c2, p1) in [(p0.x, c1.x, c2.x, p1.x), (p0.y, c1.y, c2.y, p1.y)] {
+            // Roots of the derivative divided by three: at^2 + bt + c
+            let a = p1 - 3. * c2 + 3. * c1 - p0;
+            let b = 2. * (c2 - 2. * c1 + p0);
+            let c = c1 - p0;
+            let mut roots = [f32::NAN; 2];
+            if a == 0. {
+                if b != 0. {
+                    roots[0] = -c / b;
+                }
+            } else {
+                let disc = b * b - 4. * a * c;
+                if disc >= 0. {
+                    let sqrt = disc.sqrt();
+                    let q = -0.5 * if b < 0. { b - sqrt } else { b + sqrt };
+                    roots[0] = q / a;
+                    if q != 0. {
+                        roots[1] = c / q;
+                    }
+                }
+            }
+            for t in roots {
+                if t > 0. && t < 1. {
+                    self.add(eval(t));
+                }
+            }
+        }
+        self
+    }
+
+    /// Returns true if the point lies within the bounds accumulated so far.
+    fn contains(&self, p: Point) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x && p.y >= self.min.y && p.y <= self.max.y
+    }
+
     pub fn build(&self) -> Bounds {
         if self.count != 0 {
             Bounds {
@@ -14,6 +14,7 @@ pub struct HitTest<'a, 's, D> {
     style: Style<'a>,
     transform: Option<Transform>,
     threshold: u8,
+    aliased: bool,
     scratch: RefCell<Option<&'s mut Scratch>>,
 }
 
@@ -28,6 +29,7 @@ where
             style: Style::Fill(Fill::NonZero),
             transform: None,
             threshold: 0,
+            aliased: false,
             scratch: RefCell::new(None),
         }
     }
@@ -39,6 +41,7 @@ where
             style: Style::Fill(Fill::NonZero),
             transform: None,
             threshold: 0,
+            aliased: false,
             scratch: RefCell::new(Some(scratch)),
         }
     }
@@ -61,16 +64,30 @@ where
         self
     }
 
+    /// Enables aliased testing. When enabled, the point is treated as the
+    /// center of a pixel rendered with [`Mask::aliased`], so the test
+    /// registers exactly when the point is inside the path. The default is
+    /// false, in which case the coverage of the pixel with its minimum corner
+    /// at the point is compared against the threshold.
+    pub fn aliased(&mut self, aliased: bool) -> &mut Self {
+        self.aliased = aliased;
+        self
+    }
+
     /// Returns true if the specified point is painted by the path.
     pub fn test(&self, point: impl Into<Point>) -> bool {
         let mut scratch = self.scratch.borrow_mut();
         let mut buf = [0u8; 1];
-        let p = point.into() * -1.;
+        let mut p = point.into() * -1.;
+        if self.aliased {
+            p = p + Point::new(0.5, 0.5);
+        }
         if let Some(scratch) = scratch.as_mut() {
             Mask::with_scratch(&self.data, scratch)
                 .style(self.style)
                 .offset(p)
                 .transform(self.transform)
+                .aliased(self.aliased)
                 .size(1, 1)
                 .render_into(&mut buf, None);
         } else {
@@ -78,6 +95,7 @@ where
                 .style(self.style)
                 .offset(p)
                 .transform(self.transform)
+                .aliased(self.aliased)
                 .size(1, 1)
                 .render_into(&mut buf, None);
         }
@@ -4,9 +4,15 @@ with support for rendering paths of various styles into alpha or subpixel
 masks.
 
 Broadly speaking, support is provided for the following:
-- 256x anti-aliased rasterization (8-bit alpha or 32-bit RGBA subpixel alpha)
+- Anti-aliased rasterization (8-bit, 16-bit or floating point alpha or RGBA
+  subpixel alpha)
+- Signed distance field and multi-channel signed distance field generation
+- Aliased rasterization and 1-bit monochrome masks
+- Texture atlas packing with padding, eviction and growth
+- Solid color painting into RGBA, BGRA and grayscale pixel buffers
+- Conservative tile coverage classification for tiled compositing
 - Pixel perfect hit testing with customizable coverage threshold
-- Non-zero and even-odd fills
+- Non-zero, even-odd, positive, negative and abs-geq-two fills
 - Stroking with the standard set of joins and caps
   (separate start and end caps are possible)
 - Numerically stable dashing for smooth dash offset animation
@@ -125,6 +131,264 @@ let (mask, placement) = Mask::new("M 8,56 32,8 56,56 Z")
     .render();
 ```
 
+For compositing directly into some other surface, the mask can also be
+rendered as a sequence of horizontal spans of uniform coverage, avoiding the
+intermediate buffer entirely:
+
+```rust
+use zeno::{Mask, PathData};
+
+let mut covered = 0;
+
+Mask::new("M 8,56 32,8 56,56 Z")
+    .size(64, 64)
+    // Invoked with the row, column, length and coverage of each span
+    .render_spans(|_y, _x, len, _coverage| covered += len);
+```
+
+Subpixel formats render one color channel per subpixel. The style is applied
+to the path once and each channel matches an alpha mask rendered with the
+offset of that subpixel:
+
+```rust
+use zeno::{Format, Mask, PathData, Stroke};
+
+// Red and blue are offset horizontally by 0.3 pixels on either side of green
+let (subpixel, placement) = Mask::new("M 3,2 Q 20,-4 28,14 L 6,29 Z")
+    .style(Stroke::new(2.5))
+    .format(Format::Subpixel)
+    .render();
+assert_eq!(subpixel.len(), placement.width as usize * placement.height as usize * 4);
+```
+
+Masks for several paths can also be accumulated into a single buffer. The
+[`Combine`] mode determines how coverage is merged with the existing contents
+of the target. For example, to compute the intersection of two squares:
+
+```rust
+use zeno::{Combine, Mask, PathData};
+
+let mut mask = [0u8; 16 * 16];
+
+Mask::new("M 0,0 12,0 12,12 0,12 Z")
+    .size(16, 16)
+    .render_into(&mut mask, None);
+Mask::new("M 4,4 16,4 16,16 4,16 Z")
+    .size(16, 16)
+    .combine(Combine::Multiply)
+    .render_into(&mut mask, None);
+
+assert_eq!(mask[2 * 16 + 2], 0);
+assert_eq!(mask[8 * 16 + 8], 255);
+assert_eq!(mask[14 * 16 + 14], 0);
+```
+
+Similarly, an existing mask can be used to clip the rendered coverage, which
+is useful for nested clipping regions:
+
+```rust
+use zeno::{Mask, PathData};
+
+// Render the clipping region
+let (clip, clip_placement) = Mask::new("M 0,0 8,0 8,16 0,16 Z").render();
+
+// Coverage is multiplied by the clip mask during rasterization
+let (mask, placement) = Mask::new("M 4,4 12,4 12,12 4,12 Z")
+    .clip_mask(&clip, clip_placement.width as usize, clip_placement)
+    .render();
+
+assert_eq!(mask[0], 255);
+assert_eq!(mask[7], 0);
+assert_eq!(placement.width, 8);
+```
+
+The placement of a mask is computed from the bounds of the geometry, so the
+mask may contain empty borders. The [`Mask::render_with_coverage`] function
+additionally reports the bounds of the pixels that were actually covered
+along with the total covered area, and [`Mask::trim`] crops the mask to those
+bounds:
+
+```rust
+use zeno::{Mask, PathData};
+
+let (_, placement, coverage) = Mask::new("M 4,4 12,4 12,12 4,12 Z")
+    .size(16, 16)
+    .render_with_coverage();
+
+assert_eq!(placement.width, 16);
+assert_eq!(coverage.area, 64.0);
+let bounds = coverage.bounds.unwrap();
+assert_eq!((bounds.left, bounds.top, bounds.width), (4, 4, 8));
+
+let (mask, placement) = Mask::new("M 4,4 12,4 12,12 4,12 Z")
+    .size(16, 16)
+    .trim(true)
+    .render();
+
+assert_eq!((placement.left, placement.width), (4, 8));
+assert_eq!(mask.len(), 64);
+```
+
+Signed distance fields, commonly used for scalable text rendering on the GPU,
+are produced by selecting the [`Format::Sdf`] format. The field is computed
+from the geometry of the path and the mask is padded by the spread:
+
+```rust
+use zeno::{Format, Mask, PathData};
+
+let (field, placement) = Mask::new("M 0,0 16,0 16,16 0,16 Z")
+    .format(Format::Sdf { spread: 4.0 })
+    .render();
+
+assert_eq!(placement.width, 24);
+// Far outside the path
+assert_eq!(field[0], 0);
+// Far inside the path
+assert_eq!(field[12 * 24 + 12], 255);
+```
+
+The [`Format::Msdf`] format produces a multi-channel field in RGBA order that
+preserves sharp corners when the median of the red, green and blue channels
+is used for reconstruction.
+
+For devices that display one bit per pixel, the [`Format::Mono`] format
+packs each row into bytes, most significant bit first. Pixels are set based
+on a coverage threshold, or by sampling pixel centers when combined with
+[`Mask::aliased`]:
+
+```rust
+use zeno::{Format, Mask, PathData};
+
+let (mask, _) = Mask::new("M 0,0 4,0 4,2 0,2 Z")
+    .size(10, 2)
+    .format(Format::Mono { threshold: 127 })
+    .render();
+
+// Each row of 10 pixels occupies two bytes
+assert_eq!(mask, [0b1111_0000, 0, 0b1111_0000, 0]);
+assert_eq!(Format::Mono { threshold: 127 }.buffer_size(10, 2), 4);
+```
+
+The [`Format::Winding8`] and [`Format::Winding16`] formats produce the signed
+winding number of the path at each pixel center rather than coverage, which
+is useful for visualizing overlaps or implementing custom fill rules:
+
+```rust
+use zeno::{Format, Mask, PathData};
+
+// Two overlapping squares with the same orientation
+let (mask, _) = Mask::new("M 0,0 2,0 2,2 0,2 Z M 1,0 3,0 3,2 1,2 Z")
+    .size(3, 1)
+    .format(Format::Winding8)
+    .render();
+
+assert_eq!(mask, [1, 2, 1]);
+```
+
+Masks wider or taller than 32767 pixels, along with geometry that lies far
+from the mask, are supported by the wide coordinate mode of the rasterizer.
+This mode is enabled automatically for large masks and may be requested
+explicitly with [`Mask::wide`]:
+
+```rust
+use zeno::{Command, Mask, PathBuilder};
+
+// A 40000x2 mask with a path spanning the full width
+let mut path: Vec<Command> = Vec::new();
+path.move_to([0.5, 0.0]).line_to([39999.5, 0.0]).line_to([39999.5, 2.0]).line_to([0.5, 2.0]).close();
+let (mask, _) = Mask::new(&path).size(40000, 2).render();
+
+// Half covered pixels at each end
+assert!(mask[0].abs_diff(128) <= 1);
+assert!(mask[39999].abs_diff(128) <= 1);
+assert_eq!(mask[20000], 255);
+
+// A triangle with vertices a billion pixels away that covers the mask
+let mut path: Vec<Command> = Vec::new();
+path.move_to([-1e9, -1e9]).line_to([1e9, 0.0]).line_to([-1e9, 1e9]).close();
+let (mask, _) = Mask::new(&path).size(16, 16).wide(true).render();
+
+assert!(mask.iter().all(|&coverage| coverage == 255));
+```
+
+Large masks may be divided into horizontal bands that are rendered
+concurrently with [`Mask::threads`]. The output is identical to rendering on
+a single thread:
+
+```rust
+# #[cfg(feature = "std")] {
+use zeno::{Mask, PathData};
+
+let path = "M 8,56 32,8 56,56 Z";
+let (mask, _) = Mask::new(path).size(64, 64).threads(4).render();
+
+assert_eq!(mask, Mask::new(path).size(64, 64).render().0);
+# }
+```
+
+When rendering untrusted input, the [`Mask::try_render`] and
+[`Mask::try_render_into`] functions validate the path, style, dimensions and
+target buffer and report failures to allocate the mask and any intermediate
+buffers, returning an [`Error`] rather than panicking or producing undefined
+output:
+
+```rust
+use zeno::{Command, Error, Format, Mask, PathBuilder, Stroke};
+
+// A 10x10 mask does not fit in a 64 byte buffer
+let mut buffer = [0u8; 64];
+let result = Mask::new("M 0,0 10,0 10,10 Z").try_render_into(&mut buffer, None);
+assert_eq!(result.unwrap_err(), Error::BufferTooSmall);
+
+let result = Mask::new("M 0,0 10,0 10,10 Z")
+    .style(Stroke::new(-1.0))
+    .try_render();
+assert_eq!(result.unwrap_err(), Error::InvalidStroke);
+
+let mut path: Vec<Command> = Vec::new();
+path.move_to([0.0, 0.0]).line_to([f32::NAN, 10.0]).close();
+assert_eq!(Mask::new(&path).try_render().unwrap_err(), Error::NonFiniteGeometry);
+
+// The size of this mask in bytes is not representable
+let size = Format::SubpixelF32.try_buffer_size(u32::MAX, u32::MAX);
+assert_eq!(size.unwrap_err(), Error::DimensionsTooLarge);
+```
+
+Alternatively, the [`Mask::sanitize`] function selects a [`Sanitize`] policy
+that skips or clamps non-finite subpaths instead. The same policies are
+available for any path data with the [`Sanitized`] adapter:
+
+```rust
+use zeno::{Command, Mask, PathBuilder, Sanitize};
+
+let mut path: Vec<Command> = Vec::new();
+path.move_to([0.0, 0.0]).line_to([10.0, 0.0]).line_to([10.0, 10.0]).close();
+path.move_to([0.0, 0.0]).line_to([f32::NAN, 10.0]).close();
+
+let (mask, placement) = Mask::new(&path)
+    .sanitize(Sanitize::SkipSubpath)
+    .try_render()
+    .unwrap();
+assert_eq!(placement.width, 10);
+```
+
+For simple renderers that draw solid colors, the [`Canvas`] type fills and
+strokes paths directly into an RGBA, BGRA or grayscale pixel buffer, blending
+each span of coverage without an intermediate mask:
+
+```rust
+use zeno::{BlendMode, Canvas, Fill, PixelFormat};
+
+let mut pixels = [0u8; 8 * 8];
+Canvas::new(&mut pixels, 8, 8, 8, PixelFormat::Gray8)
+    .unwrap()
+    .fill("M 0,0 8,0 8,8 0,8 Z", Fill::NonZero, [255, 255, 255, 255])
+    .blend_mode(BlendMode::Clear)
+    .fill("M 0,0 4,0 4,8 0,8 Z", Fill::NonZero, [0, 0, 0, 255]);
+
+assert_eq!(pixels[..8], [0, 0, 0, 0, 255, 255, 255, 255]);
+```
+
 Both [`Mask`] and [`Stroke`] offer large sets of options for fine-grained
 control of styling and rasterization including offsets, scaling,
 transformations, formats, coordinate spaces and more. See
@@ -165,6 +429,23 @@ hit_test.threshold(0);
 assert_eq!(hit_test.test([2, 0]), true);
 ```
 
+Alternatively, aliased hit testing registers exactly when the point is inside
+the path. This matches the output of masks rendered with [`Mask::aliased`],
+which sample each pixel at its center to produce hard edges:
+
+```rust
+use zeno::{HitTest, Mask, PathData};
+
+let path = "M2.5,0 2.5,2 5,2 5,0 Z";
+let (mask, _) = Mask::new(path).size(5, 2).aliased(true).render();
+assert_eq!(mask[..5], [0, 0, 255, 255, 255]);
+
+let mut hit_test = HitTest::new(path);
+hit_test.aliased(true);
+assert_eq!(hit_test.test([2.5, 0.5]), true);
+assert_eq!(hit_test.test([2.4, 0.5]), false);
+```
+
 See the [`HitTest`] type for more detail.
 
 # Path building
@@ -259,6 +540,11 @@ heap allocated storage and provides replacement methods for functions that may
 allocate. In addition, the [`Mask::with_scratch`] and [`HitTest::with_scratch`]
 constructors are provided which take a scratch instance as an argument and
 redirect all transient allocations to the reusable storage.
+
+When rendering many paths with the same settings, such as when populating a
+glyph cache, the [`Batch`] builder accepts an iterator of path, style and
+transform jobs and renders each into a separate mask, reusing scratch memory
+across jobs and optionally dividing the work among several threads.
  */
 
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -268,7 +554,14 @@ compile_error! { "Either the std or libm feature must be enabled"  }
 
 extern crate alloc;
 
+#[cfg(feature = "eval")]
+mod atlas;
+#[cfg(feature = "eval")]
+mod batch;
+#[cfg(feature = "eval")]
+mod canvas;
 mod command;
+mod error;
 mod geometry;
 #[cfg(feature = "eval")]
 mod hit_test;
@@ -279,30 +572,47 @@ mod path_data;
 #[cfg(feature = "eval")]
 mod raster;
 #[cfg(feature = "eval")]
+mod sample;
+mod sanitize;
+#[cfg(feature = "eval")]
 mod scratch;
+#[cfg(feature = "eval")]
+mod sdf;
 mod segment;
 #[cfg(feature = "eval")]
 mod stroke;
 mod style;
 mod svg_parser;
 #[cfg(feature = "eval")]
+mod tile;
+#[cfg(feature = "eval")]
 mod traversal;
 
+#[cfg(feature = "eval")]
+pub use atlas::{Atlas, AtlasEntry, AtlasId};
+#[cfg(feature = "eval")]
+pub use batch::Batch;
+#[cfg(feature = "eval")]
+pub use canvas::{BlendMode, Canvas, PixelFormat};
 pub use command::{Command, Verb};
+pub use error::Error;
 pub use geometry::{Angle, Bounds, Origin, Placement, Point, Transform, Vector};
 #[cfg(feature = "eval")]
 pub use hit_test::HitTest;
 #[cfg(feature = "eval")]
-pub use mask::{Format, Mask};
+pub use mask::{Combine, Coverage, CoverageCurve, Format, LcdFilter, Mask, Span, Spans};
 pub use path_builder::{ArcSize, ArcSweep, PathBuilder};
 #[cfg(feature = "eval")]
-pub use path_data::{apply, bounds};
+pub use path_data::{apply, bounds, loose_bounds};
 pub use path_data::{length, PathData};
+pub use sanitize::{Sanitize, Sanitized};
 #[cfg(feature = "eval")]
 pub use scratch::Scratch;
 pub use style::*;
 pub use svg_parser::validate_svg;
 #[cfg(feature = "eval")]
+pub use tile::{TileCoverage, TileMask};
+#[cfg(feature = "eval")]
 pub use traversal::{Vertex, Vertices, Walk};
 
 macro_rules! define_f32_ext {
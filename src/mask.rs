@@ -1,9 +1,16 @@
 //! Mask generator.
 
-use super::geometry::{Origin, Placement, Transform, Vector};
+use super::command::Command;
+use super::error::Error;
+use super::geometry::{Bounds, Origin, Placement, Transform, Vector};
 use super::path_data::{apply, PathData};
+use super::raster::{FixedPoint, RasterStorage, Rasterizer, MAX_DIM, MAX_WIDE_DIM};
+use super::sample::Sampler;
+use super::sanitize::{is_finite, Sanitize, Sanitized};
 use super::scratch::Scratch;
+use super::sdf::Field;
 use super::style::{Fill, Style};
+use super::tile::{classify, TileCoverage};
 #[allow(unused)]
 use super::F32Ext;
 
@@ -11,15 +18,83 @@ use crate::lib::Vec;
 use core::cell::RefCell;
 
 /// The desired output image format for rendering.
-#[derive(Copy, Clone, PartialEq, Debug)]
+///
+/// Multi-byte samples are stored in native byte order.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[non_exhaustive]
 pub enum Format {
     /// 8-bit alpha mask.
+    #[default]
     Alpha,
+    /// 16-bit alpha mask.
+    Alpha16,
+    /// 32-bit floating point alpha mask with coverage in the range 0..=1.
+    AlphaF32,
     /// 32-bit RGBA subpixel mask with 1/3 pixel offsets for the red and
     /// blue channels.
     Subpixel,
+    /// 64-bit RGBA subpixel mask with 16 bits per channel and the same
+    /// offsets as [`Self::Subpixel`].
+    Subpixel16,
+    /// 128-bit RGBA subpixel mask with 32-bit floating point channels and
+    /// the same offsets as [`Self::Subpixel`].
+    SubpixelF32,
     /// 32-bit RGBA subpixel mask with custom offsets.
     CustomSubpixel([f32; 3]),
+    /// 32-bit RGBA subpixel mask with custom two dimensional offsets. This
+    /// supports panels with vertically stacked subpixels.
+    CustomSubpixelOffsets([Vector; 3]),
+    /// 8-bit signed distance field computed from the geometry of the path.
+    ///
+    /// The spread is the distance in pixels from the edge of the path at
+    /// which the field saturates. Distances are mapped linearly such that 0
+    /// is at least `spread` pixels outside of the path, 255 is at least
+    /// `spread` pixels inside and the edge lies at 127.5. When the size of the
+    /// mask is computed from the bounds of the path, the mask is padded by
+    /// the spread on each side. Coverage curves, LCD filters and clip masks
+    /// do not apply to this format.
+    Sdf {
+        /// Distance in pixels covered by the field on each side of the edge.
+        spread: f32,
+    },
+    /// 32-bit RGBA multi-channel signed distance field computed from the
+    /// geometry of the path.
+    ///
+    /// The edges of the path are assigned to color channels such that the
+    /// median of the red, green and blue channels reconstructs sharp corners.
+    /// The alpha channel contains the true signed distance. Distances are
+    /// encoded and padded in the same manner as [`Self::Sdf`].
+    Msdf {
+        /// Distance in pixels covered by the field on each side of the edge.
+        spread: f32,
+    },
+    /// 1-bit monochrome mask with eight pixels per byte, ordered from the
+    /// most significant bit. Each row begins on a byte boundary.
+    ///
+    /// A pixel is set when its 8-bit coverage exceeds the threshold, or
+    /// reaches it for a threshold of 255, in the same manner as
+    /// [`HitTest`](super::HitTest). For aliased masks, pixels are set when
+    /// their centers are inside the path. When combining with the existing
+    /// contents of the target buffer, [`Combine::Max`] and [`Combine::Add`]
+    /// set bits, [`Combine::Multiply`] clears bits that are not set and
+    /// [`Combine::Subtract`] clears bits that are set. LCD filters do not
+    /// apply to this format.
+    Mono {
+        /// Coverage value that must be exceeded for a pixel to be set.
+        threshold: u8,
+    },
+    /// 8-bit signed winding number of the path at the center of each pixel,
+    /// saturated to the range of `i8`.
+    ///
+    /// The fill rule is ignored and winding numbers are positive where
+    /// [`Fill::Positive`] covers the path. [`Combine::Add`] and
+    /// [`Combine::Subtract`] accumulate winding numbers with the existing
+    /// contents of the target buffer and other modes replace them. Coverage
+    /// curves, LCD filters and clip masks do not apply to this format.
+    Winding8,
+    /// 16-bit signed winding number in native byte order. See
+    /// [`Self::Winding8`] for details.
+    Winding16,
 }
 
 impl Format {
@@ -28,24 +103,390 @@ impl Format {
         Self::CustomSubpixel([0.3, 0., -0.3])
     }
 
+    /// Creates a format for subpixel rendering on panels with vertically
+    /// stacked subpixels in RGB order.
+    pub fn subpixel_vrgb() -> Self {
+        Self::CustomSubpixelOffsets([Vector::new(0., -0.3), Vector::ZERO, Vector::new(0., 0.3)])
+    }
+
+    /// Creates a format for subpixel rendering on panels with vertically
+    /// stacked subpixels in BGR order.
+    pub fn subpixel_vbgr() -> Self {
+        Self::CustomSubpixelOffsets([Vector::new(0., 0.3), Vector::ZERO, Vector::new(0., -0.3)])
+    }
+
     /// Returns the necessary buffer size to hold an image of the specified
     /// width and height with this format.
+    ///
+    /// Panics if the size is not representable. Use
+    /// [`Self::try_buffer_size`] for dimensions that are not trusted.
     pub fn buffer_size(self, width: u32, height: u32) -> usize {
-        (width
-            * height
-            * match self {
-                Self::Alpha => 1,
-                _ => 4,
-            }) as usize
+        self.try_buffer_size(width, height)
+            .expect("buffer size overflow")
+    }
+
+    /// Returns the necessary buffer size to hold an image of the specified
+    /// width and height with this format, or [`Error::DimensionsTooLarge`]
+    /// if the size is not representable.
+    pub fn try_buffer_size(self, width: u32, height: u32) -> Result<usize, Error> {
+        self.checked_pitch(width)
+            .and_then(|pitch| pitch.checked_mul(height as usize))
+            .ok_or(Error::DimensionsTooLarge)
+    }
+
+    /// Returns the number of bytes in a tightly packed row of the specified
+    /// width.
+    pub(crate) fn pitch(self, width: u32) -> usize {
+        if let Self::Mono { .. } = self {
+            return (width as usize).div_ceil(8);
+        }
+        width as usize * self.channels() * self.depth().size()
+    }
+
+    /// Returns the number of bytes in a tightly packed row of the specified
+    /// width, or `None` if the size overflows.
+    fn checked_pitch(self, width: u32) -> Option<usize> {
+        if let Self::Mono { .. } = self {
+            return Some(self.pitch(width));
+        }
+        (width as usize).checked_mul(self.channels() * self.depth().size())
+    }
+
+    /// Returns the number of channels per pixel.
+    fn channels(self) -> usize {
+        if self.subpixel_offsets().is_some() || matches!(self, Self::Msdf { .. }) {
+            4
+        } else {
+            1
+        }
+    }
+
+    /// Returns the sample depth of the format.
+    pub(crate) fn depth(self) -> Depth {
+        match self {
+            Self::Alpha16 | Self::Subpixel16 | Self::Winding16 => Depth::U16,
+            Self::AlphaF32 | Self::SubpixelF32 => Depth::F32,
+            _ => Depth::U8,
+        }
+    }
+
+    /// Returns the offsets of the red, green and blue channels for subpixel
+    /// formats.
+    pub(crate) fn subpixel_offsets(self) -> Option<[Vector; 3]> {
+        const SUBPIXEL: [Vector; 3] = [Vector::new(-0.3, 0.), Vector::ZERO, Vector::new(0.3, 0.)];
+        match self {
+            Self::Alpha | Self::Alpha16 | Self::AlphaF32 => None,
+            Self::Sdf { .. } | Self::Msdf { .. } | Self::Mono { .. } => None,
+            Self::Winding8 | Self::Winding16 => None,
+            Self::Subpixel | Self::Subpixel16 | Self::SubpixelF32 => Some(SUBPIXEL),
+            Self::CustomSubpixel(subpx) => Some([
+                Vector::new(subpx[0], 0.),
+                Vector::new(subpx[1], 0.),
+                Vector::new(subpx[2], 0.),
+            ]),
+            Self::CustomSubpixelOffsets(subpx) => Some(subpx),
+        }
+    }
+}
+
+/// Transfer curve that is applied to coverage values during rendering.
+///
+/// Curves are sampled at 256 evenly spaced coverage values. For formats with
+/// more than 8 bits of precision, the result is linearly interpolated between
+/// the samples.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub enum CoverageCurve<'a> {
+    /// Coverage is emitted unmodified.
+    #[default]
+    Linear,
+    /// Coverage is raised to the power of `1 / gamma`. Values greater than
+    /// one increase the weight of thin features such as text.
+    Gamma(f32),
+    /// Coverage is boosted by the enhanced contrast function
+    /// `c * (k + 1) / (c * k + 1)` with the specified non-negative `k`.
+    Contrast(f32),
+    /// Coverage is mapped through a user supplied table indexed by 8-bit
+    /// coverage.
+    Table(&'a [u8; 256]),
+}
+
+impl CoverageCurve<'_> {
+    /// Returns the transfer table for the curve or `None` if the curve is
+    /// linear.
+    fn table(&self) -> Option<[u8; 256]> {
+        let mut table = [0u8; 256];
+        for (i, v) in table.iter_mut().enumerate() {
+            let c = i as f32 / 255.;
+            let c = match *self {
+                Self::Linear => return None,
+                Self::Table(table) => return Some(*table),
+                Self::Gamma(gamma) => c.powf(1. / gamma),
+                Self::Contrast(k) => c * (k + 1.) / (c * k + 1.),
+            };
+            *v = if c.is_finite() {
+                (c.clamp(0., 1.) * 255. + 0.5) as u8
+            } else {
+                0
+            };
+        }
+        Some(table)
+    }
+}
+
+/// Filter that is applied across neighboring subpixels to reduce color
+/// fringes in subpixel masks.
+///
+/// The filters operate along the axis of the subpixel offsets of the format,
+/// treating the channels of a row (or column) of pixels as a single sequence
+/// of samples. For 8-bit formats, the results match those of the
+/// corresponding FreeType filters.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum LcdFilter {
+    /// No filtering is applied.
+    #[default]
+    None,
+    /// The default 5-tap FIR filter used by FreeType.
+    Default,
+    /// A lighter 3-tap FIR filter that preserves more contrast.
+    Light,
+    /// The legacy intra-pixel filter used by older versions of FreeType.
+    Legacy,
+    /// A 5-tap FIR filter with custom weights. The weights are divided by 256
+    /// and should sum to that value to preserve brightness.
+    Custom([u8; 5]),
+}
+
+impl LcdFilter {
+    /// Returns true if the filter spreads coverage into neighboring pixels.
+    fn spreads(self) -> bool {
+        !matches!(self, Self::None | Self::Legacy)
+    }
+
+    /// Applies the filter to a sequence of subpixel samples. When exact is
+    /// true, samples are quantized to 8 bits and filtered with the same
+    /// arithmetic as FreeType such that 8-bit results match exactly.
+    fn apply(self, samples: &[u16], out: &mut [u16], exact: bool) {
+        let sample = |s: u16| if exact { (s >> 8) as u64 } else { s as u64 };
+        let result = |sum: u64, shift: u32| {
+            if exact {
+                (sum >> shift).min(255) as u16 * 257
+            } else {
+                (sum >> shift).min(65535) as u16
+            }
+        };
+        let weights = match self {
+            Self::None => {
+                out.copy_from_slice(samples);
+                return;
+            }
+            Self::Legacy => {
+                const R: [u64; 3] = [65538 * 9 / 13, 65538 * 3 / 13, 65538 / 13];
+                const G: [u64; 3] = [65538 / 6, 65538 * 4 / 6, 65538 / 6];
+                const B: [u64; 3] = [65538 / 13, 65538 * 3 / 13, 65538 * 9 / 13];
+                for (s, o) in samples.chunks_exact(3).zip(out.chunks_exact_mut(3)) {
+                    for (v, w) in o.iter_mut().zip([R, G, B]) {
+                        let sum = sample(s[0]) * w[0] + sample(s[1]) * w[1] + sample(s[2]) * w[2];
+                        *v = result(sum, 16);
+                    }
+                }
+                return;
+            }
+            Self::Default => [0x08, 0x4D, 0x56, 0x4D, 0x08],
+            Self::Light => [0x00, 0x55, 0x56, 0x55, 0x00],
+            Self::Custom(weights) => weights,
+        };
+        let len = samples.len() as isize;
+        for (i, v) in out.iter_mut().enumerate() {
+            let mut sum = 0;
+            for (j, &w) in weights.iter().enumerate() {
+                let k = i as isize + j as isize - 2;
+                if k >= 0 && k < len {
+                    sum += sample(samples[k as usize]) * w as u64;
+                }
+            }
+            *v = result(sum, 8);
+        }
+    }
+}
+
+/// Mode for combining rendered coverage with the existing contents of the
+/// target buffer.
+///
+/// Modes other than [`Combine::Replace`] allow masks for many paths to be
+/// accumulated into a single buffer with [`Mask::render_into`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Combine {
+    /// Coverage replaces the existing value for covered pixels.
+    #[default]
+    Replace,
+    /// Maximum of the coverage and the existing value.
+    Max,
+    /// Sum of the coverage and the existing value, saturating at full
+    /// coverage.
+    Add,
+    /// Product of the coverage and the existing value. Pixels within the
+    /// mask that are not covered by the path are cleared, producing the
+    /// intersection of the two masks.
+    Multiply,
+    /// Existing value multiplied by the inverse of the coverage.
+    Subtract,
+}
+
+/// Existing 8-bit mask that rendered coverage is multiplied by.
+#[derive(Copy, Clone)]
+struct ClipMask<'a> {
+    data: &'a [u8],
+    pitch: usize,
+    placement: Placement,
+}
+
+impl ClipMask<'_> {
+    /// Returns the clip mask with a placement relative to the first pixel of
+    /// a mask with the specified placement.
+    fn relative_to(&self, placement: &Placement, y_up: bool) -> Self {
+        let mut clip = *self;
+        clip.placement.left = self.placement.left - placement.left;
+        clip.placement.top = if y_up {
+            placement.top - self.placement.top
+        } else {
+            self.placement.top - placement.top
+        };
+        clip
+    }
+
+    /// Inverse of [`Self::relative_to`].
+    #[cfg(feature = "std")]
+    fn absolute_to(&self, placement: &Placement, y_up: bool) -> Self {
+        let mut clip = *self;
+        clip.placement.left = self.placement.left + placement.left;
+        clip.placement.top = if y_up {
+            placement.top - self.placement.top
+        } else {
+            self.placement.top + placement.top
+        };
+        clip
+    }
+
+    /// Multiplies the coverage of a span by the corresponding samples of the
+    /// clip mask and invokes the closure with each run of uniform clipped
+    /// coverage.
+    fn apply(
+        &self,
+        y: usize,
+        x: usize,
+        count: usize,
+        coverage: u16,
+        write: &mut impl FnMut(usize, usize, usize, u16),
+    ) {
+        let Placement {
+            left,
+            top,
+            width,
+            height,
+        } = self.placement;
+        let cy = y as i64 - top as i64;
+        if cy < 0 || cy >= height as i64 {
+            return write(y, x, count, 0);
+        }
+        let start = cy as usize * self.pitch;
+        let row = match self.data.get(start..start + width as usize) {
+            Some(row) => row,
+            _ => return write(y, x, count, 0),
+        };
+        let mut start = x;
+        let mut current = 0;
+        for i in x..x + count {
+            let cx = i as i64 - left as i64;
+            let c = if cx >= 0 && cx < width as i64 {
+                mul_u16(coverage, row[cx as usize] as u16 * 257)
+            } else {
+                0
+            };
+            if i == x {
+                current = c;
+            } else if c != current {
+                write(y, start, i - start, current);
+                start = i;
+                current = c;
+            }
+        }
+        if count != 0 {
+            write(y, start, x + count - start, current);
+        }
+    }
+}
+
+/// Size of a single sample in a mask.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Depth {
+    U8,
+    U16,
+    F32,
+}
+
+impl Depth {
+    /// Returns the size of a sample in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            Self::U8 => 1,
+            Self::U16 => 2,
+            Self::F32 => 4,
+        }
     }
 }
 
-impl Default for Format {
-    fn default() -> Self {
-        Self::Alpha
+/// Horizontal run of pixels with uniform coverage.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Span {
+    /// Row of the span relative to the top of the mask.
+    pub y: u32,
+    /// Column of the first pixel in the span.
+    pub x: u32,
+    /// Number of pixels in the span.
+    pub len: u32,
+    /// Coverage value for all pixels in the span.
+    pub coverage: u8,
+}
+
+/// Iterator over the spans of a rendered mask.
+///
+/// This owns a list of spans collected by [`Mask::spans`].
+#[derive(Clone)]
+pub struct Spans {
+    spans: alloc::vec::IntoIter<Span>,
+}
+
+impl Iterator for Spans {
+    type Item = Span;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.spans.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.spans.size_hint()
     }
 }
 
+impl ExactSizeIterator for Spans {}
+
+/// Coverage statistics of a rendered mask.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Coverage {
+    /// Smallest rectangle that contains all pixels with non-zero coverage,
+    /// in the same coordinate space as the placement of the mask, or `None`
+    /// if no pixels are covered.
+    pub bounds: Option<Placement>,
+    /// Sum of the coverage of all pixels, where a fully covered pixel
+    /// contributes one. With a linear coverage curve, this is the covered
+    /// area in pixels.
+    pub area: f64,
+}
+
+/// Maximum number of dash pattern repetitions accepted by the fallible
+/// render functions.
+const MAX_DASHES: f32 = (1 << 20) as f32;
+
 /// Builder for configuring and rendering a mask.
 pub struct Mask<'a, 's, D> {
     data: D,
@@ -60,6 +501,18 @@ pub struct Mask<'a, 's, D> {
     explicit_size: bool,
     has_size: bool,
     bounds_offset: Vector,
+    clip: Option<Placement>,
+    curve: CoverageCurve<'a>,
+    lcd_filter: LcdFilter,
+    combine: Combine,
+    clip_mask: Option<ClipMask<'a>>,
+    wide: bool,
+    sanitize: Option<Sanitize>,
+    trim: bool,
+    aliased: bool,
+    #[cfg(feature = "std")]
+    threads: usize,
+    shared: Option<&'a Geometry>,
     scratch: RefCell<Option<&'s mut Scratch>>,
 }
 
@@ -82,6 +535,18 @@ where
             explicit_size: false,
             has_size: false,
             bounds_offset: Vector::ZERO,
+            clip: None,
+            curve: CoverageCurve::Linear,
+            lcd_filter: LcdFilter::None,
+            combine: Combine::Replace,
+            clip_mask: None,
+            wide: false,
+            sanitize: None,
+            trim: false,
+            aliased: false,
+            #[cfg(feature = "std")]
+            threads: 1,
+            shared: None,
             scratch: RefCell::new(None),
         }
     }
@@ -101,6 +566,18 @@ where
             explicit_size: false,
             has_size: false,
             bounds_offset: Vector::ZERO,
+            clip: None,
+            curve: CoverageCurve::Linear,
+            lcd_filter: LcdFilter::None,
+            combine: Combine::Replace,
+            clip_mask: None,
+            wide: false,
+            sanitize: None,
+            trim: false,
+            aliased: false,
+            #[cfg(feature = "std")]
+            threads: 1,
+            shared: None,
             scratch: RefCell::new(Some(scratch)),
         }
     }
@@ -124,6 +601,93 @@ where
         self
     }
 
+    /// Sets the transfer curve that is applied to coverage values while
+    /// rendering. The default is a linear curve.
+    pub fn coverage_curve(&mut self, curve: CoverageCurve<'a>) -> &mut Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Sets the filter that is applied across neighboring subpixels for
+    /// subpixel formats. The default is no filtering. When the size of the
+    /// mask is computed from the bounds of the path, filters that spread
+    /// coverage extend the mask by one pixel on each side along the subpixel
+    /// axis.
+    pub fn lcd_filter(&mut self, filter: LcdFilter) -> &mut Self {
+        self.lcd_filter = filter;
+        self
+    }
+
+    /// Sets the mode for combining coverage with the existing contents of the
+    /// target buffer when rendering. The default is [`Combine::Replace`].
+    pub fn combine(&mut self, mode: Combine) -> &mut Self {
+        self.combine = mode;
+        self
+    }
+
+    /// Enables wide coordinate rasterization. The default rasterizer uses
+    /// 24.8 fixed point coordinates throughout and is limited to masks of
+    /// 32767 pixels in each dimension and to geometry within a similar
+    /// distance of the mask. In wide mode, path segments are clipped to the
+    /// mask in floating point before conversion to fixed point so geometry
+    /// may lie at any distance from the mask, and long lines are subdivided
+    /// to avoid overflow. This mode is enabled automatically for masks that
+    /// exceed 32767 pixels in either dimension and supports masks of up to
+    /// 262144 pixels in each dimension.
+    ///
+    /// Masks rendered in wide mode are always rendered on a single thread.
+    pub fn wide(&mut self, wide: bool) -> &mut Self {
+        self.wide = wide;
+        self
+    }
+
+    /// Enables aliased rendering. When enabled, each pixel is either fully
+    /// covered or uncovered depending on whether its center lies inside the
+    /// path. Centers that lie exactly on an edge are inside if the edge is a
+    /// left or top edge. The result matches [`HitTest`](super::HitTest) in
+    /// aliased mode at the pixel centers. Subpixel formats sample each
+    /// channel at its offset and distance field formats are unaffected. The
+    /// default is false.
+    pub fn aliased(&mut self, aliased: bool) -> &mut Self {
+        self.aliased = aliased;
+        self
+    }
+
+    /// Sets the policy for sanitizing the path data before rendering. See
+    /// [`Sanitized`] for details. The default is no sanitization, in which
+    /// case the path data must not contain infinite or NaN coordinates.
+    /// Wide mode is enabled implicitly by [`Sanitize::Clamp`].
+    pub fn sanitize(&mut self, policy: Sanitize) -> &mut Self {
+        self.sanitize = Some(policy);
+        self
+    }
+
+    /// Enables trimming of masks rendered to newly allocated buffers. When
+    /// enabled, the mask is cropped to the smallest rectangle that contains
+    /// all pixels with non-zero coverage and the placement is adjusted
+    /// accordingly. A mask with no coverage is trimmed to an empty size. The
+    /// default is false.
+    ///
+    /// This affects [`Self::render`], [`Self::try_render`] and
+    /// [`Self::render_with_coverage`]. Size computations are unchanged so
+    /// the mask is still rendered at its full size before cropping.
+    pub fn trim(&mut self, trim: bool) -> &mut Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Sets the number of threads used for rendering. When greater than one,
+    /// the mask is split into horizontal bands that are rendered concurrently
+    /// with separate storage. The output is identical to rendering on a
+    /// single thread. Masks rendered in wide mode, including those that
+    /// exceed 32767 pixels in either dimension, ignore this setting. The
+    /// default is one.
+    #[cfg(feature = "std")]
+    pub fn threads(&mut self, count: usize) -> &mut Self {
+        self.threads = count;
+        self
+    }
+
     /// Sets the origin that defines the coordinate system for the mask.
     pub fn origin(&mut self, origin: Origin) -> &mut Self {
         self.origin = origin;
@@ -149,6 +713,35 @@ where
         self
     }
 
+    /// Restricts rendering to a window of the path's coordinate space, after
+    /// applying any relevant style, offset and transform. The `left` and `top`
+    /// fields of the rectangle describe its minimum corner. The resulting mask
+    /// has the size of the window, overriding any size set with
+    /// [`Self::size`], and geometry outside of the window is discarded early
+    /// in the rasterizer. This is useful for rendering a large path as a
+    /// sequence of tiles.
+    pub fn clip(&mut self, rect: Placement) -> &mut Self {
+        self.clip = Some(rect);
+        self.width = rect.width;
+        self.height = rect.height;
+        self.has_size = true;
+        self
+    }
+
+    /// Sets an existing 8-bit alpha mask that rendered coverage is multiplied
+    /// by. The pitch is the number of bytes per row of the mask and the
+    /// placement describes its position in the same coordinate space as the
+    /// placement of this mask. Coverage outside of the clip mask is discarded.
+    /// This is useful for rendering nested clip paths.
+    pub fn clip_mask(&mut self, mask: &'a [u8], pitch: usize, placement: Placement) -> &mut Self {
+        self.clip_mask = Some(ClipMask {
+            data: mask,
+            pitch,
+            placement,
+        });
+        self
+    }
+
     /// Sets an additional rendering offset for the mask. This offset does not
     /// affect bounds or size computations and is only applied during
     /// rendering.
@@ -173,37 +766,206 @@ where
     /// tightly packed.
     pub fn render_into(&self, buffer: &mut [u8], pitch: Option<usize>) -> Placement {
         let (offset, placement) = self.placement();
-        let pitch = match pitch {
-            Some(pitch) => pitch,
-            _ => {
-                placement.width as usize
-                    * match self.format {
-                        Format::Alpha => 1,
-                        _ => 4,
-                    }
-            }
-        };
-        render(self, offset, &placement, buffer, pitch);
+        let pitch = pitch.unwrap_or_else(|| self.format.pitch(placement.width));
+        render(self, offset, &placement, buffer, pitch).expect("allocation failed");
         placement
     }
 
     /// Renders the mask to a newly allocated buffer.
     pub fn render(&self) -> (Vec<u8>, Placement) {
+        if self.trim {
+            let (buf, placement, _) = self.render_with_coverage();
+            return (buf, placement);
+        }
         let mut buf = Vec::new();
         let (offset, placement) = self.placement();
         buf.resize(
             self.format.buffer_size(placement.width, placement.height),
             0,
         );
-        let pitch = placement.width as usize
-            * match self.format {
-                Format::Alpha => 1,
-                _ => 4,
-            };
-        render(self, offset, &placement, &mut buf, pitch);
+        let pitch = self.format.pitch(placement.width);
+        render(self, offset, &placement, &mut buf, pitch).expect("allocation failed");
         (buf, placement)
     }
 
+    /// Renders the mask into a byte buffer and computes statistics for the
+    /// coverage of the result. See [`Self::render_into`] for a description
+    /// of the pitch.
+    ///
+    /// The statistics are computed from the contents of the buffer after
+    /// rendering, so they reflect any combine mode, coverage curve and clip
+    /// mask. A pixel is considered covered if any of its samples is non-zero.
+    /// For subpixel formats, the coverage of a pixel is the average of the
+    /// color channels and for distance field formats, the true distance
+    /// values are treated as coverage. Set pixels of monochrome masks and
+    /// pixels with non-zero winding numbers are fully covered.
+    pub fn render_into_with_coverage(
+        &self,
+        buffer: &mut [u8],
+        pitch: Option<usize>,
+    ) -> (Placement, Coverage) {
+        let placement = self.render_into(buffer, pitch);
+        let pitch = pitch.unwrap_or_else(|| self.format.pitch(placement.width));
+        let y_up = self.origin == Origin::BottomLeft;
+        let coverage = measure(self.format, buffer, pitch, &placement, y_up);
+        (placement, coverage)
+    }
+
+    /// Renders the mask to a newly allocated buffer and computes statistics
+    /// for the coverage of the result. See [`Self::render_into_with_coverage`]
+    /// for details.
+    pub fn render_with_coverage(&self) -> (Vec<u8>, Placement, Coverage) {
+        let (offset, placement) = self.placement();
+        let mut buf = alloc::vec![0; self.format.buffer_size(placement.width, placement.height)];
+        let pitch = self.format.pitch(placement.width);
+        render(self, offset, &placement, &mut buf, pitch).expect("allocation failed");
+        self.finish(buf, placement)
+    }
+
+    /// Renders the mask into a byte buffer, returning an error if the path,
+    /// style or dimensions cannot be rendered, the buffer is too small or an
+    /// intermediate buffer cannot be allocated.
+    /// See [`Self::render_into`] for a description of the pitch.
+    ///
+    /// Unless wide mode is enabled, geometry must lie within 32767 pixels of
+    /// an explicitly sized or clipped mask. In this case, the bounds of the
+    /// path are computed to enforce this limit.
+    pub fn try_render_into(
+        &self,
+        buffer: &mut [u8],
+        pitch: Option<usize>,
+    ) -> Result<Placement, Error> {
+        let (offset, placement) = self.try_placement()?;
+        let row = self
+            .format
+            .checked_pitch(placement.width)
+            .ok_or(Error::DimensionsTooLarge)?;
+        let pitch = pitch.unwrap_or(row);
+        if placement.height != 0 {
+            let size = (placement.height as usize - 1)
+                .checked_mul(pitch)
+                .and_then(|size| size.checked_add(row));
+            if pitch < row || size.is_none_or(|size| size > buffer.len()) {
+                return Err(Error::BufferTooSmall);
+            }
+        }
+        render(self, offset, &placement, buffer, pitch)?;
+        Ok(placement)
+    }
+
+    /// Renders the mask to a newly allocated buffer, returning an error if
+    /// the path, style or dimensions cannot be rendered or the buffer or any
+    /// intermediate buffers cannot be allocated. See [`Self::try_render_into`]
+    /// for details.
+    pub fn try_render(&self) -> Result<(Vec<u8>, Placement), Error> {
+        let (offset, placement) = self.try_placement()?;
+        let size = self
+            .format
+            .try_buffer_size(placement.width, placement.height)?;
+        let mut buf = try_zeroed(size)?;
+        let pitch = self.format.pitch(placement.width);
+        render(self, offset, &placement, &mut buf, pitch)?;
+        if self.trim {
+            let (buf, placement, _) = self.finish(buf, placement);
+            return Ok((buf, placement));
+        }
+        Ok((buf, placement))
+    }
+
+    /// Renders the mask as a sequence of spans, invoking the closure with the
+    /// row, starting column, length and coverage of each span. Pixels that
+    /// are not covered by the path are never reported. Spans within a row are
+    /// emitted in increasing column order and rows are emitted from the top
+    /// of the mask down, or bottom up when the origin is bottom left.
+    ///
+    /// Spans always carry 8-bit alpha coverage and the format of the mask
+    /// is ignored.
+    pub fn render_spans(&self, mut f: impl FnMut(u32, u32, u32, u8)) -> Placement {
+        let (offset, placement) = self.placement();
+        render_spans(self, offset, &placement, &mut |y, x, count, coverage| {
+            f(y as u32, x as u32, count as u32, coverage)
+        });
+        placement
+    }
+
+    /// Renders the mask to a newly allocated list of spans and returns an
+    /// iterator over the result. See [`Self::render_spans`] for details.
+    ///
+    /// The iterator is not lazy: the entire mask is rasterized and every span
+    /// is collected into a vector before this returns. Use
+    /// [`Self::render_spans`] to process spans without allocating.
+    pub fn spans(&self) -> (Spans, Placement) {
+        let mut spans = Vec::new();
+        let placement = self.render_spans(|y, x, len, coverage| {
+            spans.push(Span {
+                y,
+                x,
+                len,
+                coverage,
+            })
+        });
+        (
+            Spans {
+                spans: spans.into_iter(),
+            },
+            placement,
+        )
+    }
+
+    /// Computes the coverage of a tightly packed mask and crops it if
+    /// trimming is enabled.
+    fn finish(&self, mut buf: Vec<u8>, placement: Placement) -> (Vec<u8>, Placement, Coverage) {
+        let y_up = self.origin == Origin::BottomLeft;
+        let pitch = self.format.pitch(placement.width);
+        let coverage = measure(self.format, &buf, pitch, &placement, y_up);
+        if !self.trim {
+            return (buf, placement, coverage);
+        }
+        let Some(bounds) = coverage.bounds else {
+            buf.clear();
+            let placement = Placement {
+                width: 0,
+                height: 0,
+                ..placement
+            };
+            return (buf, placement, coverage);
+        };
+        let x = (bounds.left - placement.left) as u32;
+        let y = if y_up {
+            placement.top - bounds.top
+        } else {
+            bounds.top - placement.top
+        } as usize;
+        let row = self.format.pitch(bounds.width);
+        if let Format::Mono { .. } = self.format {
+            // Each byte is written after all of its source bits have been
+            // read, and those lie at or after the byte itself, so the rows
+            // can be shifted in place.
+            let width = bounds.width as usize;
+            for i in 0..bounds.height as usize {
+                let src = (y + i) * pitch;
+                for (n, j) in (0..width).step_by(8).enumerate() {
+                    let mut byte = 0;
+                    for b in 0..(width - j).min(8) {
+                        let k = x as usize + j + b;
+                        if buf[src + k / 8] & (0x80 >> (k % 8)) != 0 {
+                            byte |= 0x80 >> b;
+                        }
+                    }
+                    buf[i * row + n] = byte;
+                }
+            }
+        } else {
+            let start = self.format.pitch(x);
+            for i in 0..bounds.height as usize {
+                let src = (y + i) * pitch + start;
+                buf.copy_within(src..src + row, i * row);
+            }
+        }
+        buf.truncate(row * bounds.height as usize);
+        (buf, bounds, coverage)
+    }
+
     fn ensure_size(&mut self) {
         if self.has_size {
             return;
@@ -216,7 +978,33 @@ where
         self.has_size = true;
     }
 
-    fn placement(&self) -> (Vector, Placement) {
+    /// Invokes the closure with the mask temporarily set to the specified
+    /// format and its size resolved, restoring the previous format and size
+    /// state afterward.
+    pub(crate) fn with_format<R>(&mut self, format: Format, f: impl FnOnce(&Self) -> R) -> R {
+        let saved = (
+            self.format,
+            self.width,
+            self.height,
+            self.explicit_size,
+            self.has_size,
+            self.bounds_offset,
+        );
+        self.format = format;
+        self.ensure_size();
+        let result = f(self);
+        (
+            self.format,
+            self.width,
+            self.height,
+            self.explicit_size,
+            self.has_size,
+            self.bounds_offset,
+        ) = saved;
+        result
+    }
+
+    pub(crate) fn placement(&self) -> (Vector, Placement) {
         let mut placement = Placement {
             left: 0,
             top: 0,
@@ -224,26 +1012,156 @@ where
             height: self.height,
         };
         let mut offset = self.offset;
-        if self.explicit_size {
-            return (offset, placement);
-        } else if !self.has_size {
-            let mut scratch = self.scratch.borrow_mut();
-            let mut bounds = if let Some(scratch) = scratch.as_mut() {
-                scratch.bounds(&self.data, self.style, self.transform)
+        if let Some(clip) = self.clip {
+            placement.width = clip.width;
+            placement.height = clip.height;
+            placement.left = clip.left;
+            placement.top = if self.origin == Origin::BottomLeft {
+                clip.top + clip.height as i32
             } else {
-                super::bounds(&self.data, self.style, self.transform)
+                clip.top
             };
-            bounds.min = (bounds.min + self.offset).floor();
-            bounds.max = (bounds.max + self.offset).ceil();
-            offset = Vector::new(-bounds.min.x, -bounds.min.y);
-            placement.width = bounds.width() as u32;
-            placement.height = bounds.height() as u32;
+            return (offset, placement);
+        } else if self.explicit_size {
+            return (offset, placement);
+        } else if !self.has_size {
+            return self.placement_for(self.bounds());
         } else {
             offset = self.bounds_offset;
         }
         placement.left = -offset.x as i32;
         placement.top = if self.origin == Origin::BottomLeft {
-            (-offset.y).floor() + self.height as f32
+            (-offset.y).floor() + placement.height as f32
+        } else {
+            -offset.y
+        } as i32;
+        (offset, placement)
+    }
+
+    /// Validates the path, style and dimensions of the mask and computes the
+    /// offset and placement.
+    fn try_placement(&self) -> Result<(Vector, Placement), Error> {
+        if let Style::Stroke(stroke) = &self.style {
+            let dashes = stroke.dashes;
+            let valid = stroke.width.is_finite()
+                && stroke.width >= 0.
+                && stroke.miter_limit.is_finite()
+                && stroke.miter_limit >= 0.
+                && stroke.offset.is_finite()
+                && dashes.iter().all(|dash| dash.is_finite() && *dash >= 0.)
+                && (dashes.is_empty() || dashes.iter().sum::<f32>() > 0.);
+            if !valid {
+                return Err(Error::InvalidStroke);
+            }
+        }
+        let finite = |v: Vector| v.x.is_finite() && v.y.is_finite();
+        let transform = self.transform.unwrap_or_default();
+        let geometry_finite = [
+            transform.xx,
+            transform.xy,
+            transform.yx,
+            transform.yy,
+            transform.x,
+            transform.y,
+        ]
+        .iter()
+        .all(|v| v.is_finite())
+            && finite(self.offset)
+            && finite(self.render_offset)
+            && (matches!(self.sanitize, Some(Sanitize::SkipSubpath | Sanitize::Clamp))
+                || is_finite(self.data.commands()));
+        if !geometry_finite {
+            return Err(Error::NonFiniteGeometry);
+        }
+        if let Style::Stroke(stroke) = &self.style {
+            if !stroke.dashes.is_empty() {
+                // Dashes are evaluated in device space for unscaled strokes.
+                let transform = if stroke.scale { None } else { self.transform };
+                let length = super::length(self.path(), transform);
+                let period = stroke.dashes.iter().sum::<f32>();
+                if length / period > MAX_DASHES {
+                    return Err(Error::InvalidStroke);
+                }
+            }
+        }
+        let auto = self.clip.is_none() && !self.explicit_size && !self.has_size;
+        let (offset, placement) = if auto {
+            let bounds = self.bounds();
+            if !finite(bounds.min) || !finite(bounds.max) {
+                return Err(Error::NonFiniteGeometry);
+            }
+            self.placement_for(bounds)
+        } else {
+            self.placement()
+        };
+        let max_dim = placement.width.max(placement.height);
+        if max_dim > MAX_WIDE_DIM {
+            return Err(Error::DimensionsTooLarge);
+        }
+        self.format
+            .try_buffer_size(placement.width, placement.height)?;
+        // Sizes computed by inspect are derived from the bounds.
+        let fitted = auto || (self.clip.is_none() && !self.explicit_size);
+        if !fitted && !self.is_wide() && max_dim <= MAX_DIM {
+            let bounds = self.bounds();
+            if !finite(bounds.min) || !finite(bounds.max) {
+                return Err(Error::NonFiniteGeometry);
+            }
+            let shift = offset + self.render_offset;
+            let (min, max) = self.window(&placement);
+            let limit = MAX_DIM as f32;
+            if bounds.min.x + shift.x < min.x as f32 - limit
+                || bounds.min.y + shift.y < min.y as f32 - limit
+                || bounds.max.x + shift.x > max.x as f32 + limit
+                || bounds.max.y + shift.y > max.y as f32 + limit
+            {
+                return Err(Error::DimensionsTooLarge);
+            }
+        }
+        Ok((offset, placement))
+    }
+
+    /// Computes the bounds of the path with the style and transform applied.
+    fn bounds(&self) -> Bounds {
+        let mut scratch = self.scratch.borrow_mut();
+        if let Some(scratch) = scratch.as_mut() {
+            scratch.bounds(self.path(), self.style, self.transform)
+        } else {
+            super::bounds(self.path(), self.style, self.transform)
+        }
+    }
+
+    /// Computes the offset and placement of a mask that is sized to fit the
+    /// specified bounds.
+    fn placement_for(&self, mut bounds: Bounds) -> (Vector, Placement) {
+        bounds.min = (bounds.min + self.offset).floor();
+        bounds.max = (bounds.max + self.offset).ceil();
+        if let Some(subpx) = self.format.subpixel_offsets() {
+            if self.lcd_filter.spreads() {
+                let pad = if is_vertical(&subpx) {
+                    Vector::new(0., 1.)
+                } else {
+                    Vector::new(1., 0.)
+                };
+                bounds.min = bounds.min - pad;
+                bounds.max = bounds.max + pad;
+            }
+        }
+        if let Format::Sdf { spread } | Format::Msdf { spread } = self.format {
+            let pad = spread.max(0.).ceil();
+            bounds.min = bounds.min - Vector::new(pad, pad);
+            bounds.max = bounds.max + Vector::new(pad, pad);
+        }
+        let offset = Vector::new(-bounds.min.x, -bounds.min.y);
+        let mut placement = Placement {
+            left: 0,
+            top: 0,
+            width: bounds.width() as u32,
+            height: bounds.height() as u32,
+        };
+        placement.left = -offset.x as i32;
+        placement.top = if self.origin == Origin::BottomLeft {
+            (-offset.y).floor() + placement.height as f32
         } else {
             -offset.y
         } as i32;
@@ -251,6 +1169,96 @@ where
     }
 }
 
+impl<D: PathData> Mask<'_, '_, D> {
+    /// Returns the path data with the sanitization policy applied.
+    fn path(&self) -> Sanitized<&D> {
+        Sanitized::with_policy(&self.data, self.sanitize)
+    }
+
+    /// Applies the style and transform to the path, returning the resulting
+    /// commands along with the fill rule for rasterizing them.
+    fn commands(&self) -> (Vec<Command>, Fill) {
+        let mut commands = Vec::new();
+        let fill = match self.scratch.borrow_mut().as_mut() {
+            Some(scratch) => {
+                scratch
+                    .inner
+                    .apply(self.path(), &self.style, self.transform, &mut commands)
+            }
+            _ => apply(self.path(), self.style, self.transform, &mut commands),
+        };
+        (commands, fill)
+    }
+
+    /// Builds the geometry for evaluating the path at pixel centers with the
+    /// specified shift, or returns `None` if the path is rasterized.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    fn geometry(&self, shift: Vector) -> Option<Geometry> {
+        let evaluated = matches!(
+            self.format,
+            Format::Sdf { .. } | Format::Msdf { .. } | Format::Winding8 | Format::Winding16
+        );
+        if !evaluated && !self.aliased {
+            return None;
+        }
+        let (commands, fill) = self.commands();
+        Some(match self.format {
+            Format::Sdf { .. } | Format::Msdf { .. } => {
+                Geometry::Field(Field::new(&commands, shift, fill))
+            }
+            Format::Winding8 | Format::Winding16 => {
+                Geometry::Samplers(alloc::vec![Sampler::new(&commands, shift, Fill::NonZero)])
+            }
+            format => {
+                let offsets = match format.subpixel_offsets() {
+                    Some(subpx) => subpx.to_vec(),
+                    _ => alloc::vec![Vector::ZERO],
+                };
+                Geometry::Samplers(
+                    offsets
+                        .iter()
+                        .map(|offset| Sampler::new(&commands, shift + *offset, fill))
+                        .collect(),
+                )
+            }
+        })
+    }
+
+    /// Classifies the coverage of the tiles of the mask. See
+    /// [`TileMask`](super::TileMask) for details.
+    pub(crate) fn classify_tiles(&self, tile_size: (u32, u32)) -> (Vec<TileCoverage>, Placement) {
+        let (offset, placement) = self.placement();
+        let (commands, fill) = self.commands();
+        let (min, _) = self.window(&placement);
+        let shift = offset + self.render_offset - Vector::new(min.x as f32, min.y as f32);
+        let size = (placement.width, placement.height);
+        let y_up = self.origin == Origin::BottomLeft;
+        let tiles = classify(&commands, shift, fill, size, tile_size, y_up);
+        (tiles, placement)
+    }
+}
+
+impl<D> Mask<'_, '_, D> {
+    /// Returns true if the path is rasterized in wide mode, either because
+    /// it was requested or because clamped coordinates require it.
+    fn is_wide(&self) -> bool {
+        self.wide || self.sanitize == Some(Sanitize::Clamp)
+    }
+
+    /// Returns the minimum and maximum pixel coordinates of the rendering
+    /// window for the specified placement.
+    fn window(&self, placement: &Placement) -> (FixedPoint, FixedPoint) {
+        let (left, top) = match self.clip {
+            Some(clip) => (clip.left, clip.top),
+            _ => (0, 0),
+        };
+        (
+            FixedPoint::new(left, top),
+            FixedPoint::new(left + placement.width as i32, top + placement.height as i32),
+        )
+    }
+}
+
 #[allow(clippy::needless_lifetimes)]
 pub fn render<'a, 'c, D>(
     mask: &'a Mask<'a, 'c, D>,
@@ -258,198 +1266,1262 @@ pub fn render<'a, 'c, D>(
     placement: &Placement,
     buf: &mut [u8],
     pitch: usize,
-) where
+) -> Result<(), Error>
+where
     D: PathData,
 {
-    let y_up = mask.origin == Origin::BottomLeft;
-    let (is_subpx, subpx) = match mask.format {
-        Format::Alpha => (false, [Vector::ZERO; 3]),
-        Format::Subpixel => (
-            true,
-            [Vector::new(-0.3, 0.), Vector::ZERO, Vector::new(0.3, 0.)],
-        ),
-        Format::CustomSubpixel(subpx) => (
-            true,
-            [
-                Vector::new(subpx[0], 0.),
-                Vector::new(subpx[1], 0.),
-                Vector::new(subpx[2], 0.),
-            ],
-        ),
-    };
-    let fill = match mask.style {
-        Style::Fill(fill) => fill,
-        _ => Fill::NonZero,
-    };
-    let w = placement.width;
-    let h = placement.height;
-    let shift = offset + mask.render_offset;
-    let data = &mask.data;
-    let style = mask.style;
-    let transform = mask.transform;
-    let mut scratch = mask.scratch.borrow_mut();
-    use super::raster::{AdaptiveStorage, Rasterizer};
-    if let Some(scratch) = scratch.as_mut() {
-        let mut ras = Rasterizer::new(&mut scratch.render);
-        let inner = &mut scratch.inner;
-        if is_subpx {
-            ras.rasterize_write(
-                shift + subpx[0],
-                w,
-                h,
-                &mut |r| {
-                    inner.apply(data, &style, transform, r);
-                },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
-                    }
+    // Bands are rasterized relative to their own windows, which changes
+    // clipping and subdivision in wide mode, so those masks are rendered
+    // serially.
+    #[cfg(feature = "std")]
+    if mask.threads > 1
+        && placement.height > 1
+        && !mask.is_wide()
+        && placement.width.max(placement.height) <= MAX_DIM
+    {
+        let vertical_filter = mask.lcd_filter != LcdFilter::None
+            && mask
+                .format
+                .subpixel_offsets()
+                .is_some_and(|subpx| is_vertical(&subpx));
+        if !vertical_filter {
+            return render_bands(mask, offset, placement, buf, pitch);
+        }
+    }
+    match mask.format {
+        Format::Sdf { spread } => {
+            render_sdf(mask, offset, placement, buf, pitch, spread, false);
+            return Ok(());
+        }
+        Format::Msdf { spread } => {
+            render_sdf(mask, offset, placement, buf, pitch, spread, true);
+            return Ok(());
+        }
+        Format::Mono { threshold } => {
+            let combine = mask.combine;
+            let dense = combine == Combine::Multiply;
+            rasterize(
+                mask,
+                offset,
+                placement,
+                Depth::U8,
+                dense,
+                &mut |y, x, count, coverage| {
+                    let coverage = (coverage >> 8) as u8;
+                    let set = if threshold == 0xFF {
+                        coverage >= threshold
+                    } else {
+                        coverage > threshold
+                    };
+                    write_bits(&mut buf[y * pitch..], x, count, set, combine);
                 },
             );
-            ras.rasterize_write(
-                shift + subpx[1],
-                w,
-                h,
-                &mut |r| {
-                    inner.apply(data, &style, transform, r);
-                },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4 + 1;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
-                    }
-                },
+            return Ok(());
+        }
+        Format::Winding8 | Format::Winding16 => {
+            render_winding(mask, offset, placement, buf, pitch);
+            return Ok(());
+        }
+        _ => {}
+    }
+    let depth = mask.format.depth();
+    let combine = mask.combine;
+    let dense = combine == Combine::Multiply;
+    match mask.format.subpixel_offsets() {
+        Some(subpx) if mask.lcd_filter != LcdFilter::None => {
+            return render_filtered(mask, offset, placement, buf, pitch, &subpx);
+        }
+        Some(subpx)
+            if depth == Depth::U8
+                && combine == Combine::Replace
+                && mask.curve == CoverageCurve::Linear
+                && mask.clip_mask.is_none() =>
+        {
+            rasterize_subpixel_coverage(
+                mask,
+                offset,
+                placement,
+                &subpx,
+                false,
+                Output::<fn(usize, usize, usize, usize, u16)>::Direct(buf, pitch),
             );
-            ras.rasterize_write(
-                shift + subpx[2],
-                w,
-                h,
-                &mut |r| {
-                    inner.apply(data, &style, transform, r);
-                },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4 + 2;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
+        }
+        Some(subpx) => {
+            let table = mask.curve.table();
+            let exact = depth == Depth::U8;
+            let y_up = mask.origin == Origin::BottomLeft;
+            let clip_mask = mask.clip_mask.map(|clip| clip.relative_to(placement, y_up));
+            rasterize_subpixel_coverage(
+                mask,
+                offset,
+                placement,
+                &subpx,
+                dense,
+                Output::Spans(|channel, y, x, count, coverage| {
+                    let coverage = match &table {
+                        Some(table) => transfer(table, coverage, exact),
+                        _ => coverage,
+                    };
+                    let mut write = |y: usize, x: usize, count, coverage| {
+                        write_span(
+                            &mut buf[y * pitch..],
+                            depth,
+                            x * 4 + channel,
+                            count,
+                            4,
+                            coverage,
+                            combine,
+                        );
+                    };
+                    match &clip_mask {
+                        Some(clip) => clip.apply(y, x, count, coverage, &mut write),
+                        _ => write(y, x, count, coverage),
                     }
-                },
+                }),
             );
-        } else {
-            ras.rasterize(
-                shift,
-                w,
-                h,
-                &mut |r| {
-                    inner.apply(data, &style, transform, r);
+        }
+        _ if depth == Depth::U8
+            && combine == Combine::Replace
+            && mask.curve == CoverageCurve::Linear
+            && mask.clip_mask.is_none()
+            && !mask.aliased =>
+        {
+            rasterize_u8(mask, offset, placement, buf, pitch);
+        }
+        _ => {
+            rasterize(
+                mask,
+                offset,
+                placement,
+                depth,
+                dense,
+                &mut |y, x, count, coverage| {
+                    write_span(&mut buf[y * pitch..], depth, x, count, 1, coverage, combine);
                 },
-                fill,
-                buf,
-                pitch,
-                y_up,
             );
         }
+    }
+    Ok(())
+}
+
+/// Path geometry that is evaluated at pixel centers rather than rasterized.
+/// When rendering on multiple threads, this is built once and shared by each
+/// band.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+enum Geometry {
+    /// Field for the distance field formats.
+    Field(Field),
+    /// Samplers for the winding number formats and aliased rendering, one
+    /// for each subpixel offset.
+    Samplers(Vec<Sampler>),
+}
+
+/// Renders the mask in horizontal bands on separate threads. The style is
+/// applied once and each band rasterizes the resulting commands through a
+/// clipped window with the same shift, producing identical output to
+/// rendering on a single thread. Geometry that is evaluated at pixel centers
+/// is built once and each band evaluates only its own rows.
+#[cfg(feature = "std")]
+#[allow(clippy::needless_lifetimes)]
+fn render_bands<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    buf: &mut [u8],
+    pitch: usize,
+) -> Result<(), Error>
+where
+    D: PathData,
+{
+    let (commands, fill) = mask.commands();
+    let commands = &commands[..];
+    let geometry = mask.geometry(offset + mask.render_offset);
+    let (min, _) = mask.window(placement);
+    let height = placement.height as usize;
+    let rows = height.div_ceil(mask.threads.min(height));
+    let y_up = mask.origin == Origin::BottomLeft;
+    let clip_mask = mask.clip_mask.map(|clip| clip.relative_to(placement, y_up));
+    std::thread::scope(|scope| {
+        let mut bands = Vec::new();
+        let mut rest = buf;
+        let mut start = 0;
+        while start < height {
+            let end = (start + rows).min(height);
+            let len = if end == height {
+                rest.len()
+            } else {
+                ((end - start) * pitch).min(rest.len())
+            };
+            let (target, tail) = core::mem::take(&mut rest).split_at_mut(len);
+            rest = tail;
+            let top = if y_up { height - end } else { start };
+            let clip = Placement {
+                left: min.x,
+                top: min.y + top as i32,
+                width: placement.width,
+                height: (end - start) as u32,
+            };
+            let mut band = Mask {
+                data: commands,
+                style: Style::Fill(fill),
+                transform: None,
+                format: mask.format,
+                origin: mask.origin,
+                offset,
+                render_offset: mask.render_offset,
+                width: clip.width,
+                height: clip.height,
+                explicit_size: true,
+                has_size: true,
+                bounds_offset: Vector::ZERO,
+                clip: Some(clip),
+                curve: mask.curve,
+                lcd_filter: mask.lcd_filter,
+                combine: mask.combine,
+                clip_mask: None,
+                wide: false,
+                sanitize: None,
+                trim: false,
+                aliased: mask.aliased,
+                threads: 1,
+                shared: geometry.as_ref(),
+                scratch: RefCell::new(None),
+            };
+            let (band_offset, band_placement) = band.placement();
+            band.clip_mask = clip_mask.map(|mut clip| {
+                clip.placement.top -= start as i32;
+                clip.absolute_to(&band_placement, y_up)
+            });
+            bands.push(
+                scope.spawn(move || render(&band, band_offset, &band_placement, target, pitch)),
+            );
+            start = end;
+        }
+        bands
+            .into_iter()
+            .try_for_each(|band| band.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+    })
+}
+
+/// Renders a subpixel mask through an intermediate coverage buffer so that
+/// the LCD filter can be applied before the coverage curve.
+#[allow(clippy::needless_lifetimes)]
+fn render_filtered<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    buf: &mut [u8],
+    pitch: usize,
+    subpx: &[Vector; 3],
+) -> Result<(), Error>
+where
+    D: PathData,
+{
+    let w = placement.width as usize;
+    let h = placement.height as usize;
+    let mut coverage = try_zeroed::<u16>(w * h * 3)?;
+    rasterize_subpixel_coverage(
+        mask,
+        offset,
+        placement,
+        subpx,
+        false,
+        Output::Spans(|channel, y, x, count, c| {
+            let row = &mut coverage[y * w * 3..];
+            for i in x..x + count {
+                row[i * 3 + channel] = c;
+            }
+        }),
+    );
+    let filter = mask.lcd_filter;
+    let exact = mask.format.depth() == Depth::U8;
+    if is_vertical(subpx) {
+        let mut samples = try_zeroed(h * 3)?;
+        let mut filtered = try_zeroed(h * 3)?;
+        for x in 0..w {
+            for y in 0..h {
+                let i = (y * w + x) * 3;
+                samples[y * 3..y * 3 + 3].copy_from_slice(&coverage[i..i + 3]);
+            }
+            filter.apply(&samples, &mut filtered, exact);
+            for y in 0..h {
+                let i = (y * w + x) * 3;
+                coverage[i..i + 3].copy_from_slice(&filtered[y * 3..y * 3 + 3]);
+            }
+        }
+    } else {
+        let mut filtered = try_zeroed(w * 3)?;
+        for row in coverage.chunks_exact_mut(w * 3) {
+            filter.apply(row, &mut filtered, exact);
+            row.copy_from_slice(&filtered);
+        }
+    }
+    let depth = mask.format.depth();
+    let table = mask.curve.table();
+    let combine = mask.combine;
+    let y_up = mask.origin == Origin::BottomLeft;
+    let clip_mask = mask.clip_mask.map(|clip| clip.relative_to(placement, y_up));
+    for (y, row) in coverage.chunks_exact(w * 3).enumerate() {
+        let target = &mut buf[y * pitch..];
+        for (i, &c) in row.iter().enumerate() {
+            if c == 0 && combine != Combine::Multiply {
+                continue;
+            }
+            let c = match &table {
+                Some(table) => transfer(table, c, depth == Depth::U8),
+                _ => c,
+            };
+            let mut write = |_, x: usize, _, c| {
+                write_span(target, depth, x * 4 + i % 3, 1, 4, c, combine);
+            };
+            match &clip_mask {
+                Some(clip) => clip.apply(y, i / 3, 1, c, &mut write),
+                _ => write(y, i / 3, 1, c),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Allocates a zeroed buffer, returning an error rather than aborting if the
+/// memory is not available.
+fn try_zeroed<T: Copy + Default>(len: usize) -> Result<Vec<T>, Error> {
+    let mut buf = Vec::new();
+    buf.try_reserve_exact(len)
+        .map_err(|_| Error::AllocationFailed)?;
+    buf.resize(len, T::default());
+    Ok(buf)
+}
+
+/// Renders a signed distance field for the geometry of the mask. When multi
+/// is true, renders a multi-channel field with four channels per pixel.
+#[allow(clippy::needless_lifetimes)]
+fn render_sdf<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    buf: &mut [u8],
+    pitch: usize,
+    spread: f32,
+    multi: bool,
+) where
+    D: PathData,
+{
+    let built;
+    let field = match mask.shared {
+        Some(Geometry::Field(field)) => field,
+        _ => {
+            let (commands, fill) = mask.commands();
+            built = Field::new(&commands, offset + mask.render_offset, fill);
+            &built
+        }
+    };
+    let (min, _) = mask.window(placement);
+    let size = (placement.width as usize, placement.height as usize);
+    let y_up = mask.origin == Origin::BottomLeft;
+    let spread = spread.max(1. / 256.);
+    let combine = mask.combine;
+    let encode = |distance: f32| ((0.5 + 0.5 * distance / spread) * 255. + 0.5) as u8 as u16 * 257;
+    field.evaluate(
+        (min.x, min.y),
+        size,
+        spread,
+        multi,
+        &mut |x, y, distances| {
+            let y = if y_up { size.1 - 1 - y } else { y };
+            let row = &mut buf[y * pitch..];
+            if multi {
+                for (channel, distance) in distances.iter().enumerate() {
+                    write_span(
+                        row,
+                        Depth::U8,
+                        x * 4 + channel,
+                        1,
+                        1,
+                        encode(*distance),
+                        combine,
+                    );
+                }
+            } else {
+                write_span(row, Depth::U8, x, 1, 1, encode(distances[3]), combine);
+            }
+        },
+    );
+}
+
+/// Renders the winding number of the path at each pixel center.
+#[allow(clippy::needless_lifetimes)]
+fn render_winding<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    buf: &mut [u8],
+    pitch: usize,
+) where
+    D: PathData,
+{
+    let built;
+    let sampler = match mask.shared {
+        Some(Geometry::Samplers(samplers)) => &samplers[0],
+        _ => {
+            let (commands, _) = mask.commands();
+            built = Sampler::new(&commands, offset + mask.render_offset, Fill::NonZero);
+            &built
+        }
+    };
+    let (min, _) = mask.window(placement);
+    let size = (placement.width as usize, placement.height as usize);
+    let y_up = mask.origin == Origin::BottomLeft;
+    let wide = mask.format == Format::Winding16;
+    let combine = |dst: i32, src: i32| match mask.combine {
+        Combine::Add => dst + src,
+        Combine::Subtract => dst - src,
+        _ => src,
+    };
+    sampler.winding((min.x, min.y), size, y_up, &mut |y, x, count, winding| {
+        let row = &mut buf[y * pitch..];
+        for x in x..x + count {
+            if wide {
+                let j = x * 2;
+                let d = i16::from_ne_bytes([row[j], row[j + 1]]) as i32;
+                let w = combine(d, winding).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+                row[j..j + 2].copy_from_slice(&w.to_ne_bytes());
+            } else {
+                let w = combine(row[x] as i8 as i32, winding).clamp(-128, 127);
+                row[x] = w as i8 as u8;
+            }
+        }
+    });
+}
+
+/// Returns true if the subpixel offsets describe vertically stacked
+/// subpixels.
+fn is_vertical(subpx: &[Vector; 3]) -> bool {
+    let d = subpx[2] - subpx[0];
+    d.y.abs() > d.x.abs()
+}
+
+#[allow(clippy::needless_lifetimes)]
+pub fn render_spans<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    write: &mut impl FnMut(usize, usize, usize, u8),
+) where
+    D: PathData,
+{
+    rasterize(
+        mask,
+        offset,
+        placement,
+        Depth::U8,
+        false,
+        &mut |y, x, count, coverage| write(y, x, count, (coverage >> 8) as u8),
+    );
+}
+
+/// Rasterizes the path described by the mask, invoking the closure with the
+/// row, column, length and 16-bit coverage of each span after applying the
+/// coverage curve. Coverage is quantized to 8 bits before the curve is applied
+/// when the target depth is 8 bits.
+#[allow(clippy::needless_lifetimes)]
+fn rasterize<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    depth: Depth,
+    dense: bool,
+    write: &mut impl FnMut(usize, usize, usize, u16),
+) where
+    D: PathData,
+{
+    let y_up = mask.origin == Origin::BottomLeft;
+    let clip_mask = mask.clip_mask.map(|clip| clip.relative_to(placement, y_up));
+    let table = mask.curve.table();
+    let exact = depth == Depth::U8;
+    rasterize_coverage(
+        mask,
+        offset,
+        placement,
+        dense,
+        &mut |y, x, count, coverage| {
+            let coverage = match &table {
+                Some(table) => transfer(table, coverage, exact),
+                _ => coverage,
+            };
+            match &clip_mask {
+                Some(clip) => clip.apply(y, x, count, coverage, write),
+                _ => write(y, x, count, coverage),
+            }
+        },
+    );
+}
+
+/// Rasterizes the path described by the mask, invoking the closure with the
+/// row, column, length and 16-bit coverage of each span.
+#[allow(clippy::needless_lifetimes)]
+fn rasterize_coverage<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    dense: bool,
+    write: &mut impl FnMut(usize, usize, usize, u16),
+) where
+    D: PathData,
+{
+    let y_up = mask.origin == Origin::BottomLeft;
+    let fill = match mask.style {
+        Style::Fill(fill) => fill,
+        _ => Fill::NonZero,
+    };
+    let (min, max) = mask.window(placement);
+    let shift = offset + mask.render_offset;
+    if mask.aliased {
+        return sample_coverage(
+            mask,
+            shift,
+            placement,
+            &[Vector::ZERO],
+            dense,
+            &mut |_, y, x, count, coverage| write(y, x, count, coverage),
+        );
+    }
+    let data = mask.path();
+    let style = mask.style;
+    let transform = mask.transform;
+    let mut scratch = mask.scratch.borrow_mut();
+    use super::raster::AdaptiveStorage;
+    if let Some(scratch) = scratch.as_mut() {
+        let mut ras = Rasterizer::new(&mut scratch.render);
+        ras.begin(shift, min, max, mask.is_wide());
+        scratch.inner.apply(data, &style, transform, &mut ras);
+        ras.end();
+        ras.sweep(fill, y_up, dense, write);
     } else {
         let mut storage = AdaptiveStorage::new();
         let mut ras = Rasterizer::new(&mut storage);
-        if is_subpx {
-            ras.rasterize_write(
-                shift + subpx[0],
-                w,
-                h,
-                &mut |r| {
-                    apply(data, style, transform, r);
-                },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
-                    }
-                },
-            );
-            ras.rasterize_write(
-                shift + subpx[1],
-                w,
-                h,
-                &mut |r| {
-                    apply(data, style, transform, r);
+        ras.begin(shift, min, max, mask.is_wide());
+        apply(data, style, transform, &mut ras);
+        ras.end();
+        ras.sweep(fill, y_up, dense, write);
+    }
+}
+
+/// Rasterizes the path described by the mask, writing 8-bit coverage
+/// directly into the rows of the buffer. This is the fast path for the
+/// default alpha mask with no coverage curve, clip mask or combine mode.
+#[allow(clippy::needless_lifetimes)]
+fn rasterize_u8<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    buf: &mut [u8],
+    pitch: usize,
+) where
+    D: PathData,
+{
+    let y_up = mask.origin == Origin::BottomLeft;
+    let fill = match mask.style {
+        Style::Fill(fill) => fill,
+        _ => Fill::NonZero,
+    };
+    let (min, max) = mask.window(placement);
+    let shift = offset + mask.render_offset;
+    let data = mask.path();
+    let style = mask.style;
+    let transform = mask.transform;
+    let mut scratch = mask.scratch.borrow_mut();
+    use super::raster::AdaptiveStorage;
+    if let Some(scratch) = scratch.as_mut() {
+        let mut ras = Rasterizer::new(&mut scratch.render);
+        ras.begin(shift, min, max, mask.is_wide());
+        scratch.inner.apply(data, &style, transform, &mut ras);
+        ras.end();
+        ras.sweep_u8(fill, y_up, buf, pitch, 1);
+    } else {
+        let mut storage = AdaptiveStorage::new();
+        let mut ras = Rasterizer::new(&mut storage);
+        ras.begin(shift, min, max, mask.is_wide());
+        apply(data, style, transform, &mut ras);
+        ras.end();
+        ras.sweep_u8(fill, y_up, buf, pitch, 1);
+    }
+}
+
+/// Destination for the coverage of each channel of a subpixel mask.
+enum Output<'b, F> {
+    /// The closure is invoked with the channel, row, column, length and
+    /// 16-bit coverage of each span.
+    Spans(F),
+    /// 8-bit coverage is written directly into the interleaved channels of
+    /// the buffer with the specified pitch, replacing the existing values.
+    Direct(&'b mut [u8], usize),
+}
+
+impl<F: FnMut(usize, usize, usize, usize, u16)> Output<'_, F> {
+    /// Writes the coverage accumulated by the rasterizer for the specified
+    /// channel.
+    fn sweep<S: RasterStorage>(
+        &mut self,
+        channel: usize,
+        ras: &Rasterizer<S>,
+        fill: Fill,
+        y_up: bool,
+        dense: bool,
+    ) {
+        match self {
+            Self::Spans(write) => ras.sweep(fill, y_up, dense, &mut |y, x, count, coverage| {
+                write(channel, y, x, count, coverage)
+            }),
+            Self::Direct(buf, pitch) => ras.sweep_u8(fill, y_up, &mut buf[channel..], *pitch, 4),
+        }
+    }
+}
+
+/// Rasterizes the path described by the mask once for each of the subpixel
+/// offsets, writing the coverage of each channel to the output. The style is
+/// applied to the path only once and the resulting commands are accumulated
+/// by all three rasterizers.
+#[allow(clippy::needless_lifetimes)]
+fn rasterize_subpixel_coverage<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    subpx: &[Vector; 3],
+    dense: bool,
+    mut output: Output<impl FnMut(usize, usize, usize, usize, u16)>,
+) where
+    D: PathData,
+{
+    let y_up = mask.origin == Origin::BottomLeft;
+    let fill = match mask.style {
+        Style::Fill(fill) => fill,
+        _ => Fill::NonZero,
+    };
+    let (min, max) = mask.window(placement);
+    let shift = offset + mask.render_offset;
+    if mask.aliased {
+        return match output {
+            Output::Spans(mut write) => {
+                sample_coverage(mask, shift, placement, subpx, dense, &mut write)
+            }
+            Output::Direct(buf, pitch) => sample_coverage(
+                mask,
+                shift,
+                placement,
+                subpx,
+                dense,
+                &mut |channel, y, x, count, coverage| {
+                    let row = &mut buf[y * pitch..];
+                    write_span(
+                        row,
+                        Depth::U8,
+                        x * 4 + channel,
+                        count,
+                        4,
+                        coverage,
+                        Combine::Replace,
+                    );
                 },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4 + 1;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
+            ),
+        };
+    }
+    let data = mask.path();
+    let style = mask.style;
+    let transform = mask.transform;
+    let mut scratch = mask.scratch.borrow_mut();
+    use super::raster::{AdaptiveStorage, Rasterizers};
+    if let Some(scratch) = scratch.as_mut() {
+        let [storage1, storage2] = &mut scratch.subpixel;
+        let mut rasterizers = [
+            Rasterizer::new(&mut scratch.render),
+            Rasterizer::new(storage1),
+            Rasterizer::new(storage2),
+        ];
+        for (ras, subpx) in rasterizers.iter_mut().zip(subpx) {
+            ras.begin(shift + *subpx, min, max, mask.is_wide());
+        }
+        scratch
+            .inner
+            .apply(data, &style, transform, &mut Rasterizers(&mut rasterizers));
+        for (channel, ras) in rasterizers.iter_mut().enumerate() {
+            ras.end();
+            output.sweep(channel, ras, fill, y_up, dense);
+        }
+    } else {
+        let mut storage = [
+            AdaptiveStorage::new(),
+            AdaptiveStorage::new(),
+            AdaptiveStorage::new(),
+        ];
+        let [storage0, storage1, storage2] = &mut storage;
+        let mut rasterizers = [
+            Rasterizer::new(storage0),
+            Rasterizer::new(storage1),
+            Rasterizer::new(storage2),
+        ];
+        for (ras, subpx) in rasterizers.iter_mut().zip(subpx) {
+            ras.begin(shift + *subpx, min, max, mask.is_wide());
+        }
+        apply(data, style, transform, &mut Rasterizers(&mut rasterizers));
+        for (channel, ras) in rasterizers.iter_mut().enumerate() {
+            ras.end();
+            output.sweep(channel, ras, fill, y_up, dense);
+        }
+    }
+}
+
+/// Samples the path described by the mask at pixel centers once for each of
+/// the offsets, invoking the closure with the index of the offset, row,
+/// column, length and 16-bit coverage of each span.
+#[allow(clippy::needless_lifetimes)]
+fn sample_coverage<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    shift: Vector,
+    placement: &Placement,
+    offsets: &[Vector],
+    dense: bool,
+    write: &mut impl FnMut(usize, usize, usize, usize, u16),
+) where
+    D: PathData,
+{
+    let (min, _) = mask.window(placement);
+    let size = (placement.width as usize, placement.height as usize);
+    let y_up = mask.origin == Origin::BottomLeft;
+    let built: Vec<_>;
+    let samplers = match mask.shared {
+        Some(Geometry::Samplers(samplers)) if samplers.len() == offsets.len() => samplers,
+        _ => {
+            let (commands, fill) = mask.commands();
+            built = offsets
+                .iter()
+                .map(|offset| Sampler::new(&commands, shift + *offset, fill))
+                .collect();
+            &built
+        }
+    };
+    for (index, sampler) in samplers.iter().enumerate() {
+        sampler.sample(
+            (min.x, min.y),
+            size,
+            y_up,
+            dense,
+            &mut |y, x, count, coverage| write(index, y, x, count, coverage),
+        );
+    }
+}
+
+/// Maps 16-bit coverage through a 256 entry transfer table. When exact is
+/// true, coverage is quantized to 8 bits and looked up directly. Otherwise, the
+/// result is linearly interpolated between adjacent entries.
+#[inline(always)]
+fn transfer(table: &[u8; 256], coverage: u16, exact: bool) -> u16 {
+    let i = (coverage >> 8) as usize;
+    let a = table[i] as i32 * 257;
+    if exact {
+        return a as u16;
+    }
+    let b = table[(i + 1).min(255)] as i32 * 257;
+    let f = (coverage & 255) as i32;
+    (a + (((b - a) * f) >> 8)) as u16
+}
+
+/// Computes the coverage statistics of a rendered mask.
+fn measure(
+    format: Format,
+    buf: &[u8],
+    pitch: usize,
+    placement: &Placement,
+    y_up: bool,
+) -> Coverage {
+    let depth = format.depth();
+    let stride = format.channels();
+    let channels = if format.subpixel_offsets().is_some() {
+        0..3
+    } else if stride == 4 {
+        3..4
+    } else {
+        0..1
+    };
+    let scale = 1. / channels.len() as f32;
+    let size = stride * depth.size();
+    let mono = matches!(format, Format::Mono { .. });
+    let winding = matches!(format, Format::Winding8 | Format::Winding16);
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+    let mut area = 0f64;
+    for y in 0..placement.height {
+        let row = &buf[y as usize * pitch..];
+        let mut sum = 0f32;
+        for x in 0..placement.width {
+            let coverage = if mono {
+                if row[x as usize / 8] & (0x80 >> (x % 8)) == 0 {
+                    continue;
+                }
+                1.
+            } else {
+                let pixel = &row[x as usize * size..(x as usize + 1) * size];
+                if pixel.iter().all(|&b| b == 0) {
+                    continue;
+                }
+                if winding {
+                    1.
+                } else {
+                    channels
+                        .clone()
+                        .map(|channel| read_sample(pixel, depth, channel))
+                        .sum::<f32>()
+                        * scale
+                }
+            };
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            min_y = min_y.min(y);
+            max_y = max_y.max(y);
+            sum += coverage;
+        }
+        area += sum as f64;
+    }
+    if min_x > max_x {
+        return Coverage { bounds: None, area };
+    }
+    let top = if y_up {
+        placement.top - min_y as i32
+    } else {
+        placement.top + min_y as i32
+    };
+    let bounds = Placement {
+        left: placement.left + min_x as i32,
+        top,
+        width: max_x - min_x + 1,
+        height: max_y - min_y + 1,
+    };
+    Coverage {
+        bounds: Some(bounds),
+        area,
+    }
+}
+
+/// Reads a normalized sample from a row of a mask. The index is in units of
+/// samples of the specified depth.
+#[inline(always)]
+fn read_sample(row: &[u8], depth: Depth, index: usize) -> f32 {
+    match depth {
+        Depth::U8 => row[index] as f32 * (1. / 255.),
+        Depth::U16 => {
+            let j = index * 2;
+            u16::from_ne_bytes([row[j], row[j + 1]]) as f32 * (1. / 65535.)
+        }
+        Depth::F32 => {
+            let j = index * 4;
+            f32::from_ne_bytes([row[j], row[j + 1], row[j + 2], row[j + 3]])
+        }
+    }
+}
+
+/// Writes a span of 16-bit coverage into a row of the target buffer,
+/// combining it with the existing samples according to the specified mode.
+/// The index and stride are in units of samples of the specified depth.
+#[inline(always)]
+fn write_span(
+    row: &mut [u8],
+    depth: Depth,
+    index: usize,
+    count: usize,
+    stride: usize,
+    coverage: u16,
+    combine: Combine,
+) {
+    match depth {
+        Depth::U8 => {
+            let c = (coverage >> 8) as u8;
+            if combine == Combine::Replace {
+                if stride == 1 {
+                    for b in &mut row[index..index + count] {
+                        *b = c;
                     }
-                },
-            );
-            ras.rasterize_write(
-                shift + subpx[2],
-                w,
-                h,
-                &mut |r| {
-                    apply(data, style, transform, r);
-                },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4 + 2;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
+                } else {
+                    for i in 0..count {
+                        row[index + i * stride] = c;
                     }
-                },
-            );
+                }
+            } else {
+                for i in 0..count {
+                    let b = &mut row[index + i * stride];
+                    *b = combine_u8(combine, *b, c);
+                }
+            }
+        }
+        Depth::U16 => {
+            for i in 0..count {
+                let j = (index + i * stride) * 2;
+                let d = u16::from_ne_bytes([row[j], row[j + 1]]);
+                let c = combine_u16(combine, d, coverage);
+                row[j..j + 2].copy_from_slice(&c.to_ne_bytes());
+            }
+        }
+        Depth::F32 => {
+            let c = coverage as f32 * (1. / 65535.);
+            for i in 0..count {
+                let j = (index + i * stride) * 4;
+                let d = f32::from_ne_bytes([row[j], row[j + 1], row[j + 2], row[j + 3]]);
+                let c = match combine {
+                    Combine::Replace => c,
+                    Combine::Max => d.max(c),
+                    Combine::Add => (d + c).min(1.),
+                    Combine::Multiply => d * c,
+                    Combine::Subtract => d * (1. - c),
+                };
+                row[j..j + 4].copy_from_slice(&c.to_ne_bytes());
+            }
+        }
+    }
+}
+
+/// Writes a span of pixels into a row of a 1-bit mask, combining it with the
+/// existing bits according to the specified mode.
+#[inline(always)]
+fn write_bits(row: &mut [u8], index: usize, count: usize, set: bool, combine: Combine) {
+    let set = match combine {
+        Combine::Replace => set,
+        Combine::Max | Combine::Add if set => true,
+        Combine::Multiply if !set => false,
+        Combine::Subtract if set => false,
+        _ => return,
+    };
+    let end = index + count;
+    let mut i = index;
+    while i < end {
+        let bit = i % 8;
+        let n = (8 - bit).min(end - i);
+        let bits = ((0xFF00u16 >> n) as u8) >> bit;
+        if set {
+            row[i / 8] |= bits;
         } else {
-            ras.rasterize(
-                shift,
-                w,
-                h,
-                &mut |r| {
-                    apply(data, style, transform, r);
-                },
-                fill,
-                buf,
-                pitch,
-                y_up,
-            );
+            row[i / 8] &= !bits;
+        }
+        i += n;
+    }
+}
+
+#[inline(always)]
+fn combine_u8(combine: Combine, dst: u8, src: u8) -> u8 {
+    match combine {
+        Combine::Replace => src,
+        Combine::Max => dst.max(src),
+        Combine::Add => dst.saturating_add(src),
+        Combine::Multiply => mul_u8(dst, src),
+        Combine::Subtract => mul_u8(dst, 255 - src),
+    }
+}
+
+#[inline(always)]
+fn combine_u16(combine: Combine, dst: u16, src: u16) -> u16 {
+    match combine {
+        Combine::Replace => src,
+        Combine::Max => dst.max(src),
+        Combine::Add => dst.saturating_add(src),
+        Combine::Multiply => mul_u16(dst, src),
+        Combine::Subtract => mul_u16(dst, 65535 - src),
+    }
+}
+
+/// Multiplies two normalized 8-bit values with rounding.
+#[inline(always)]
+pub(super) fn mul_u8(a: u8, b: u8) -> u8 {
+    let t = a as u32 * b as u32 + 128;
+    ((t + (t >> 8)) >> 8) as u8
+}
+
+/// Multiplies two normalized 16-bit values with rounding.
+#[inline(always)]
+fn mul_u16(a: u16, b: u16) -> u16 {
+    let t = a as u32 * b as u32 + 32768;
+    ((t + (t >> 16)) >> 16) as u16
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Stroke;
+
+    #[test]
+    fn threads_match_single_thread() {
+        let path = "M 3,1 Q 60,-20 90,40 C 120,90 20,150 5,120 L 40,60 Z M 20,20 70,30 30,90 Z";
+        let formats = [
+            Format::Alpha,
+            Format::Alpha16,
+            Format::Subpixel,
+            Format::Sdf { spread: 4.0 },
+            Format::Msdf { spread: 4.0 },
+            Format::Mono { threshold: 127 },
+            Format::Winding8,
+        ];
+        for format in formats {
+            for origin in [Origin::TopLeft, Origin::BottomLeft] {
+                let (clip, clip_placement) =
+                    Mask::new("M 0,0 100,40 0,150 Z").origin(origin).render();
+                for clipped in [false, true] {
+                    for (wide, aliased) in [(false, false), (true, false), (false, true)] {
+                        let render = |threads| {
+                            let mut mask = Mask::new(path);
+                            mask.format(format)
+                                .origin(origin)
+                                .wide(wide)
+                                .aliased(aliased)
+                                .threads(threads);
+                            if clipped {
+                                let pitch = clip_placement.width as usize;
+                                mask.clip_mask(&clip, pitch, clip_placement);
+                            }
+                            mask.render().0
+                        };
+                        assert_eq!(render(1), render(7));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn direct_alpha_matches_spans() {
+        // Max into a cleared buffer takes the span path and should produce
+        // the same result as the direct path used for replace.
+        let path = "M 3,1 Q 60,-20 90,40 C 120,90 20,150 5,120 L 40,60 Z M 20,20 70,30 30,90 Z";
+        let clip = Placement {
+            left: 10,
+            top: 20,
+            width: 50,
+            height: 60,
+        };
+        for origin in [Origin::TopLeft, Origin::BottomLeft] {
+            for wide in [false, true] {
+                for clipped in [false, true] {
+                    for fill in [Fill::NonZero, Fill::EvenOdd] {
+                        let render = |combine| {
+                            let mut mask = Mask::new(path);
+                            mask.style(fill).origin(origin).wide(wide).combine(combine);
+                            if clipped {
+                                mask.clip(clip);
+                            }
+                            mask.render().0
+                        };
+                        assert_eq!(render(Combine::Replace), render(Combine::Max));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn subpixel_channels_match_offset_alpha() {
+        let path = "M 3,2 Q 20,-4 28,14 L 6,29 Z";
+        let style = Stroke::new(2.5);
+        let mut scratch = Scratch::new();
+        for use_scratch in [false, true] {
+            for origin in [Origin::TopLeft, Origin::BottomLeft] {
+                // Max into a cleared buffer takes the span path rather than
+                // writing the channels directly.
+                for combine in [Combine::Replace, Combine::Max] {
+                    let mut subpixel = [0u8; 32 * 32 * 4];
+                    let mut mask = match use_scratch {
+                        true => Mask::with_scratch(path, &mut scratch),
+                        _ => Mask::new(path),
+                    };
+                    mask.style(style)
+                        .size(32, 32)
+                        .origin(origin)
+                        .format(Format::Subpixel)
+                        .combine(combine)
+                        .render_into(&mut subpixel, None);
+                    for (channel, offset) in [-0.3, 0.0, 0.3].into_iter().enumerate() {
+                        let (alpha, _) = Mask::new(path)
+                            .style(style)
+                            .size(32, 32)
+                            .origin(origin)
+                            .render_offset([offset, 0.0])
+                            .render();
+                        for (i, &coverage) in alpha.iter().enumerate() {
+                            assert_eq!(subpixel[i * 4 + channel], coverage);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn directional_fill_rules() {
+        // Clockwise with a top left origin, so the winding number is positive.
+        let square = "M 0,0 4,0 4,4 0,4 Z";
+        let reversed = "M 0,0 0,4 4,4 4,0 Z";
+        let render = |path, fill| Mask::new(path).style(fill).size(4, 4).render().0;
+        assert_eq!(render(square, Fill::Positive), [255; 16]);
+        assert_eq!(render(reversed, Fill::Positive), [0; 16]);
+        assert_eq!(render(square, Fill::Negative), [0; 16]);
+        assert_eq!(render(reversed, Fill::Negative), [255; 16]);
+    }
+
+    #[test]
+    fn abs_geq_two_fills_overlap() {
+        // The second contour overlaps the square down to the middle of the
+        // second row. As with the non-zero rule, half coverage rounds to 127
+        // for positive winding numbers and 128 for negative ones. Contours
+        // in opposite directions cancel and leave nothing covered.
+        let overlap = [
+            ("M 0,0 4,0 4,4 0,4 Z M 0,0 4,0 4,1.5 0,1.5 Z", 127),
+            ("M 0,0 0,4 4,4 4,0 Z M 0,0 0,1.5 4,1.5 4,0 Z", 128),
+            ("M 0,0 4,0 4,4 0,4 Z M 0,0 0,1.5 4,1.5 4,0 Z", 0),
+        ];
+        for (path, half) in overlap {
+            let (mask, _) = Mask::new(path).style(Fill::AbsGeqTwo).size(4, 4).render();
+            let full = if half == 0 { 0 } else { 255 };
+            assert_eq!(mask[..4], [full; 4]);
+            assert_eq!(mask[4..8], [half; 4]);
+            assert_eq!(mask[8..], [0; 8]);
+        }
+    }
+
+    #[test]
+    fn threads_match_single_thread_when_wide() {
+        // Wide mode is enabled automatically for masks taller than 32767
+        // pixels.
+        let render = |threads| {
+            Mask::new("M 0,0 4,1 3,40000 1,39000 Z")
+                .size(4, 40000)
+                .threads(threads)
+                .render()
+                .0
+        };
+        assert_eq!(render(1), render(8));
+    }
+
+    #[test]
+    fn lcd_filters_match_freetype() {
+        // Reference outputs from FreeType 2.12 with the filtered row starting
+        // one pixel to the left of the unfiltered row.
+        let cases: [(LcdFilter, &[u8], &[u8]); 6] = [
+            (
+                LcdFilter::Default,
+                &[0, 0, 0, 255, 255, 255, 0, 0, 0],
+                &[0, 7, 84, 170, 239, 170, 84, 7, 0],
+            ),
+            (
+                LcdFilter::Default,
+                &[0, 0, 0, 255, 128, 0, 0, 0, 0],
+                &[0, 7, 80, 124, 119, 46, 4, 0, 0],
+            ),
+            (
+                LcdFilter::Light,
+                &[0, 0, 0, 255, 255, 255, 0, 0, 0],
+                &[0, 0, 84, 170, 255, 170, 84, 0, 0],
+            ),
+            (
+                LcdFilter::Light,
+                &[0, 0, 0, 255, 128, 0, 0, 0, 0],
+                &[0, 0, 84, 128, 127, 42, 0, 0, 0],
+            ),
+            (LcdFilter::Legacy, &[255, 255, 255], &[255, 255, 255]),
+            (LcdFilter::Legacy, &[255, 128, 0], &[206, 127, 49]),
+        ];
+        for (filter, input, expected) in cases {
+            let samples: Vec<u16> = input.iter().map(|&v| v as u16 * 257).collect();
+            let mut out = vec![0; samples.len()];
+            filter.apply(&samples, &mut out, true);
+            let out: Vec<u8> = out.iter().map(|&v| (v >> 8) as u8).collect();
+            assert_eq!(out, expected, "{filter:?}");
+        }
+    }
+
+    #[test]
+    fn lcd_filters_grow_subpixel_axis() {
+        let placement = |format, filter| {
+            let p = Mask::new("M 2,2 6,2 6,6 2,6 Z")
+                .format(format)
+                .lcd_filter(filter)
+                .render()
+                .1;
+            (p.left, p.top, p.width, p.height)
+        };
+        for format in [Format::Subpixel, Format::subpixel_vrgb()] {
+            let base = placement(format, LcdFilter::None);
+            assert_eq!(placement(format, LcdFilter::Legacy), base);
+            for filter in [LcdFilter::Default, LcdFilter::Light] {
+                let grown = placement(format, filter);
+                let (left, top, width, height) = base;
+                let expected = if format == Format::Subpixel {
+                    (left - 1, top, width + 2, height)
+                } else {
+                    (left, top - 1, width, height + 2)
+                };
+                assert_eq!(grown, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn gamma_on_half_covered_pixel() {
+        let render = |curve| {
+            Mask::new("M 0,0 1,0 1,0.5 0,0.5 Z")
+                .size(1, 1)
+                .coverage_curve(curve)
+                .render()
+                .0[0]
+        };
+        assert_eq!(render(CoverageCurve::Linear), 127);
+        // (127 / 255) ^ (1 / 2.2) * 255 = 185.8
+        assert_eq!(render(CoverageCurve::Gamma(2.2)), 186);
+        // (127 / 255) ^ 2.2 * 255 = 55.0
+        assert_eq!(render(CoverageCurve::Gamma(1. / 2.2)), 55);
+        assert_eq!(render(CoverageCurve::Gamma(1.)), 127);
+    }
+
+    #[test]
+    fn vertical_subpixel_channel_offsets() {
+        // An edge through the middle of the pixel: with the path shifted by
+        // the channel offsets, red covers 0.2, green 0.5 and blue 0.8 of it.
+        let render = |format| {
+            let (mask, _) = Mask::new("M -1,-1 2,-1 2,0.5 -1,0.5 Z")
+                .size(1, 1)
+                .format(format)
+                .render();
+            [mask[0], mask[1], mask[2]]
+        };
+        assert_eq!(render(Format::subpixel_vrgb()), [50, 127, 203]);
+        assert_eq!(render(Format::subpixel_vbgr()), [203, 127, 50]);
+        // Horizontal offsets leave a horizontal edge unchanged.
+        assert_eq!(render(Format::Subpixel), [127, 127, 127]);
+    }
+
+    #[test]
+    fn msdf_median_matches_inside_at_corners() {
+        // Bilinearly sampling the field near the corners of a square must
+        // reconstruct the sharp corner from the median of the channels.
+        let (mask, _) = Mask::new("M 4,4 14,4 14,14 4,14 Z")
+            .format(Format::Msdf { spread: 4.0 })
+            .size(18, 18)
+            .render();
+        let channel = |x: usize, y: usize, c: usize| mask[(y * 18 + x) * 4 + c] as f32;
+        let sample = |x: f32, y: f32, c: usize| {
+            let (x, y) = (x - 0.5, y - 0.5);
+            let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+            let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+            let top = channel(x0, y0, c) * (1. - fx) + channel(x0 + 1, y0, c) * fx;
+            let bottom = channel(x0, y0 + 1, c) * (1. - fx) + channel(x0 + 1, y0 + 1, c) * fx;
+            top * (1. - fy) + bottom * fy
+        };
+        for (cx, cy) in [(4., 4.), (14., 4.), (14., 14.), (4., 14.)] {
+            for i in -8..=8 {
+                for j in -8..=8 {
+                    let (x, y) = (cx + i as f32 * 0.125, cy + j as f32 * 0.125);
+                    if x == cx || y == cy {
+                        continue;
+                    }
+                    let [r, g, b] = [0, 1, 2].map(|c| sample(x, y, c));
+                    let median = r.max(g).min(r.min(g).max(b));
+                    let inside = (4. ..14.).contains(&x) && (4. ..14.).contains(&y);
+                    assert_eq!(median > 127.5, inside, "({x}, {y})");
+                }
+            }
         }
     }
 }
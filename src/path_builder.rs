@@ -328,9 +328,8 @@ impl PathBuilder for BoundsBuilder {
     }
 
     fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
-        self.add(control.into());
         let p = to.into();
-        self.add(p);
+        self.add_quad(self.current, control.into(), p);
         self.current = p;
         self
     }
@@ -341,10 +340,8 @@ impl PathBuilder for BoundsBuilder {
         control2: impl Into<Point>,
         to: impl Into<Point>,
     ) -> &mut Self {
-        self.add(control1.into());
-        self.add(control2.into());
         let p = to.into();
-        self.add(p);
+        self.add_cubic(self.current, control1.into(), control2.into(), p);
         self.current = p;
         self
     }
@@ -121,6 +121,17 @@ pub fn length(data: impl PathData, transform: Option<Transform>) -> f32 {
 }
 
 /// Computes the bounding box of the path.
+///
+/// The bounds are exact, including the extrema of curves rather than their
+/// control points.
+///
+/// ```rust
+/// use zeno::{bounds, loose_bounds, Fill};
+///
+/// let path = "M 0,0 Q 5,10 10,0";
+/// assert_eq!(bounds(path, Fill::NonZero, None).max.y, 5.0);
+/// assert_eq!(loose_bounds(path, Fill::NonZero, None).max.y, 10.0);
+/// ```
 #[cfg(feature = "eval")]
 pub fn bounds<'a>(
     data: impl PathData,
@@ -133,6 +144,23 @@ pub fn bounds<'a>(
     bounds.build()
 }
 
+/// Computes a conservative bounding box of the path that includes the
+/// control points of curves.
+///
+/// This is faster than [`bounds`] but may be larger for paths that contain
+/// curves.
+#[cfg(feature = "eval")]
+pub fn loose_bounds<'a>(
+    data: impl PathData,
+    style: impl Into<Style<'a>>,
+    transform: Option<Transform>,
+) -> Bounds {
+    let style = style.into();
+    let mut bounds = BoundsBuilder::loose();
+    apply(data, style, transform, &mut bounds);
+    bounds.build()
+}
+
 /// Applies the style and transform to the path and emits the result to the
 /// specified sink.
 #[cfg(feature = "eval")]
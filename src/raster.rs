@@ -9,23 +9,44 @@ use super::style::Fill;
 use crate::lib::Vec;
 use core::fmt;
 
+/// Converts accumulated area to 16-bit coverage according to the fill rule.
 #[inline(always)]
-fn coverage(fill: Fill, mut coverage: i32) -> u8 {
-    coverage >>= PIXEL_BITS * 2 + 1 - 8;
-    if fill == Fill::EvenOdd {
-        coverage &= 511;
-        if coverage >= 256 {
-            coverage = 511i32.wrapping_sub(coverage);
+fn coverage(fill: Fill, mut coverage: i32) -> u16 {
+    coverage >>= PIXEL_BITS * 2 + 1 - 16;
+    // Accumulated coverage is negative where the winding number is positive.
+    match fill {
+        Fill::NonZero => {
+            if coverage < 0 {
+                coverage = !coverage;
+            }
+            if coverage >= 65536 {
+                coverage = 65535;
+            }
         }
-    } else {
-        if coverage < 0 {
-            coverage = !coverage;
+        Fill::EvenOdd => {
+            coverage &= 131071;
+            if coverage >= 65536 {
+                coverage = 131071i32.wrapping_sub(coverage);
+            }
         }
-        if coverage >= 256 {
-            coverage = 255;
+        Fill::Positive => {
+            coverage = if coverage < 0 {
+                (!coverage).min(65535)
+            } else {
+                0
+            };
+        }
+        Fill::Negative => {
+            coverage = coverage.clamp(0, 65535);
+        }
+        Fill::AbsGeqTwo => {
+            if coverage < 0 {
+                coverage = !coverage;
+            }
+            coverage = (coverage - 65536).clamp(0, 65535);
         }
     }
-    coverage as u8
+    coverage as u16
 }
 
 pub struct Rasterizer<'a, S: RasterStorage> {
@@ -34,7 +55,6 @@ pub struct Rasterizer<'a, S: RasterStorage> {
     xmax: i32,
     ymin: i32,
     ymax: i32,
-    height: i32,
     shift: Vector,
     start: FixedPoint,
     closed: bool,
@@ -46,6 +66,9 @@ pub struct Rasterizer<'a, S: RasterStorage> {
     cover: i32,
     area: i32,
     invalid: bool,
+    wide: bool,
+    origin: (f64, f64),
+    first: Point,
 }
 
 impl<'a, S: RasterStorage> Rasterizer<'a, S> {
@@ -56,7 +79,6 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
             xmax: 0,
             ymin: 0,
             ymax: 0,
-            height: 0,
             shift: Vector::ZERO,
             start: FixedPoint::default(),
             closed: false,
@@ -68,183 +90,171 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
             cover: 0,
             area: 0,
             invalid: false,
+            wide: false,
+            origin: (0., 0.),
+            first: Point::ZERO,
         }
     }
 
-    pub fn rasterize(
-        &mut self,
-        shift: Vector,
-        width: u32,
-        height: u32,
-        apply: &mut impl FnMut(&mut Self),
-        fill: Fill,
-        buffer: &mut [u8],
-        pitch: usize,
-        y_up: bool,
-    ) {
-        let w = width as i32;
-        let h = height as i32;
-        self.storage
-            .reset(FixedPoint { x: 0, y: 0 }, FixedPoint { x: w, y: h });
+    /// Resets the rasterizer for the window described by the min and max
+    /// pixel coordinates.
+    ///
+    /// When wide is true, or the window exceeds the range of the fixed point
+    /// line setup, segments are clipped to the window in floating point and
+    /// coordinates are made relative to the window before conversion to
+    /// fixed point. This supports geometry at arbitrary distances from the
+    /// window and windows up to [`MAX_WIDE_DIM`] pixels in each dimension.
+    pub fn begin(&mut self, shift: Vector, min: FixedPoint, max: FixedPoint, wide: bool) {
+        self.wide = wide || (max.x - min.x) as u32 > MAX_DIM || (max.y - min.y) as u32 > MAX_DIM;
+        let (min, max) = if self.wide {
+            self.origin = (min.x as f64, min.y as f64);
+            (
+                FixedPoint::default(),
+                FixedPoint::new(max.x - min.x, max.y - min.y),
+            )
+        } else {
+            (min, max)
+        };
+        self.storage.reset(min, max);
         self.shift = shift;
         self.start = FixedPoint::default();
         self.closed = true;
         self.current = Point::ZERO;
-        self.xmin = 0;
-        self.ymin = 0;
-        self.xmax = w;
-        self.ymax = h;
-        self.height = h;
+        self.xmin = min.x;
+        self.ymin = min.y;
+        self.xmax = max.x;
+        self.ymax = max.y;
         self.x = 0;
         self.y = 0;
         self.px = 0;
         self.py = 0;
         self.invalid = true;
-        apply(self);
+        self.first = Point::ZERO;
+    }
+
+    /// Closes the current subpath and flushes the final cell.
+    pub fn end(&mut self) {
         if !self.closed {
-            self.line_to(self.start);
+            self.close_subpath();
         }
         if !self.invalid {
             self.storage.set(self.x, self.y, self.area, self.cover);
         }
+    }
+
+    /// Invokes the closure with the row, column, length and coverage of each
+    /// span of accumulated cells. When dense is true, spans with zero coverage
+    /// are emitted as well such that every pixel in the window is visited.
+    pub fn sweep(
+        &self,
+        fill: Fill,
+        y_up: bool,
+        dense: bool,
+        write: &mut impl FnMut(usize, usize, usize, u16),
+    ) {
         let indices = self.storage.indices();
         let cells = self.storage.cells();
         let min = FixedPoint::new(self.xmin, self.ymin);
         let max = FixedPoint::new(self.xmax, self.ymax);
-        let height = height as usize;
-        for (i, &index) in indices.iter().enumerate() {
-            if index != -1 {
-                let y = ((i as i32) - min.y) as usize;
-                let row_offset = if y_up {
-                    pitch * (height - 1 - y)
-                } else {
-                    pitch * y
-                };
-                let row = &mut buffer[row_offset..];
-                let mut x = min.x;
-                let mut cover = 0;
-                let mut area;
-                let mut index = index;
-                loop {
-                    let cell = &cells[index as usize];
-                    if cover != 0 && cell.x > x {
-                        let count = (cell.x - x) as usize;
-                        let c = coverage(fill, cover);
-                        let xi = x as usize;
-                        for b in &mut row[xi..xi + count] {
-                            *b = c;
-                        }
-                    }
-                    cover = cover.wrapping_add(cell.cover.wrapping_mul(ONE_PIXEL * 2));
-                    area = cover.wrapping_sub(cell.area);
-                    if area != 0 && cell.x >= min.x {
-                        let count = 1;
-                        let c = coverage(fill, area);
-                        let xi = cell.x as usize;
-                        for b in &mut row[xi..xi + count] {
-                            *b = c;
-                        }
-                    }
-                    x = cell.x + 1;
-                    index = cell.next;
-                    if index == -1 {
-                        break;
-                    }
+        let height = (max.y - min.y) as usize;
+        for (y, &index) in indices.iter().enumerate() {
+            let y = if y_up { height - 1 - y } else { y };
+            if index == -1 {
+                if dense {
+                    write(y, 0, (max.x - min.x) as usize, 0);
                 }
-                if cover != 0 {
-                    let count = (max.x - x) as usize;
+                continue;
+            }
+            let mut x = min.x;
+            let mut cover = 0;
+            let mut area;
+            let mut index = index;
+            loop {
+                let cell = &cells[index as usize];
+                if (cover != 0 || dense) && cell.x > x {
+                    let count = (cell.x - x) as usize;
                     let c = coverage(fill, cover);
-                    let xi = x as usize;
-                    for b in &mut row[xi..xi + count] {
-                        *b = c;
-                    }
+                    let xi = (x - min.x) as usize;
+                    write(y, xi, count, c);
+                }
+                cover = cover.wrapping_add(cell.cover.wrapping_mul(ONE_PIXEL * 2));
+                area = cover.wrapping_sub(cell.area);
+                if (area != 0 || dense) && cell.x >= min.x {
+                    let count = 1;
+                    let c = coverage(fill, area);
+                    let xi = (cell.x - min.x) as usize;
+                    write(y, xi, count, c);
                 }
+                x = cell.x + 1;
+                index = cell.next;
+                if index == -1 {
+                    break;
+                }
+            }
+            if (cover != 0 || dense) && x < max.x {
+                let count = (max.x - x) as usize;
+                let c = coverage(fill, cover);
+                let xi = (x - min.x) as usize;
+                write(y, xi, count, c);
             }
         }
     }
 
-    pub fn rasterize_write(
-        &mut self,
-        shift: Vector,
-        width: u32,
-        height: u32,
-        apply: &mut impl FnMut(&mut Self),
-        fill: Fill,
-        pitch: usize,
-        y_up: bool,
-        write: &mut impl FnMut(usize, usize, usize, u8),
-    ) {
-        let w = width as i32;
-        let h = height as i32;
-        self.storage
-            .reset(FixedPoint { x: 0, y: 0 }, FixedPoint { x: w, y: h });
-        self.shift = shift;
-        self.start = FixedPoint::default();
-        self.closed = true;
-        self.current = Point::ZERO;
-        self.xmin = 0;
-        self.ymin = 0;
-        self.xmax = w;
-        self.ymax = h;
-        self.height = h;
-        self.x = 0;
-        self.y = 0;
-        self.px = 0;
-        self.py = 0;
-        self.invalid = true;
-        apply(self);
-        if !self.closed {
-            self.line_to(self.start);
-        }
-        if !self.invalid {
-            self.storage.set(self.x, self.y, self.area, self.cover);
-        }
+    /// Writes the 8-bit coverage of each span of accumulated cells directly
+    /// into the rows of the buffer, replacing the existing values. Pixels are
+    /// separated by the specified stride, which allows writing a single
+    /// channel of an interleaved buffer. Produces the same result as a sparse
+    /// [`Self::sweep`] that stores the high byte of the coverage, without the
+    /// per-span dispatch.
+    pub fn sweep_u8(&self, fill: Fill, y_up: bool, buffer: &mut [u8], pitch: usize, stride: usize) {
         let indices = self.storage.indices();
         let cells = self.storage.cells();
         let min = FixedPoint::new(self.xmin, self.ymin);
         let max = FixedPoint::new(self.xmax, self.ymax);
-        let height = height as usize;
-        for (i, &index) in indices.iter().enumerate() {
-            if index != -1 {
-                let y = ((i as i32) - min.y) as usize;
-                let row_offset = if y_up {
-                    pitch * (height - 1 - y)
-                } else {
-                    pitch * y
-                };
-                let mut x = min.x;
-                let mut cover = 0;
-                let mut area;
-                let mut index = index;
-                loop {
-                    let cell = &cells[index as usize];
-                    if cover != 0 && cell.x > x {
-                        let count = (cell.x - x) as usize;
-                        let c = coverage(fill, cover);
-                        let xi = x as usize;
-                        write(row_offset, xi, count, c);
-                    }
-                    cover = cover.wrapping_add(cell.cover.wrapping_mul(ONE_PIXEL * 2));
-                    area = cover.wrapping_sub(cell.area);
-                    if area != 0 && cell.x >= min.x {
-                        let count = 1;
-                        let c = coverage(fill, area);
-                        let xi = cell.x as usize;
-                        write(row_offset, xi, count, c);
-                    }
-                    x = cell.x + 1;
-                    index = cell.next;
-                    if index == -1 {
-                        break;
-                    }
+        let height = (max.y - min.y) as usize;
+        let fill_span = |row: &mut [u8], start: i32, end: i32, c: u8| {
+            let (start, end) = ((start - min.x) as usize, (end - min.x) as usize);
+            if stride == 1 {
+                row[start..end].fill(c);
+            } else {
+                for b in row[start * stride..]
+                    .iter_mut()
+                    .step_by(stride)
+                    .take(end - start)
+                {
+                    *b = c;
                 }
-                if cover != 0 {
-                    let count = (max.x - x) as usize;
-                    let c = coverage(fill, cover);
-                    let xi = x as usize;
-                    write(row_offset, xi, count, c);
+            }
+        };
+        for (y, &index) in indices.iter().enumerate() {
+            if index == -1 {
+                continue;
+            }
+            let y = if y_up { height - 1 - y } else { y };
+            let row = &mut buffer[y * pitch..];
+            let mut x = min.x;
+            let mut cover = 0;
+            let mut area;
+            let mut index = index;
+            loop {
+                let cell = &cells[index as usize];
+                if cover != 0 && cell.x > x {
+                    fill_span(row, x, cell.x, (coverage(fill, cover) >> 8) as u8);
+                }
+                cover = cover.wrapping_add(cell.cover.wrapping_mul(ONE_PIXEL * 2));
+                area = cover.wrapping_sub(cell.area);
+                if area != 0 && cell.x >= min.x {
+                    row[(cell.x - min.x) as usize * stride] = (coverage(fill, area) >> 8) as u8;
+                }
+                x = cell.x + 1;
+                index = cell.next;
+                if index == -1 {
+                    break;
                 }
             }
+            if cover != 0 && x < max.x {
+                fill_span(row, x, max.x, (coverage(fill, cover) >> 8) as u8);
+            }
         }
     }
 
@@ -271,6 +281,19 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
     }
 
     fn line_to(&mut self, to: FixedPoint) {
+        if self.wide
+            && ((to.x - self.px).abs() > MAX_WIDE_LINE || (to.y - self.py).abs() > MAX_WIDE_LINE)
+        {
+            // Long lines overflow the line setup and lose precision in the
+            // reciprocal division, so split them.
+            let mid = FixedPoint::new(
+                self.px + (to.x - self.px) / 2,
+                self.py + (to.y - self.py) / 2,
+            );
+            self.line_to(mid);
+            self.line_to(to);
+            return;
+        }
         let to_x = to.x;
         let to_y = to.y;
         let mut ey1 = trunc(self.py);
@@ -281,11 +304,25 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
             return;
         }
         let mut ex1 = trunc(self.px);
-        let ex2 = trunc(to_x);
+        let mut ex2 = trunc(to_x);
+        if ex1 >= self.xmax && ex2 >= self.xmax {
+            self.px = to_x;
+            self.py = to_y;
+            return;
+        }
         let mut fx1 = fract(self.px);
         let mut fy1 = fract(self.py);
-        let dx = to_x - self.px;
+        let mut dx = to_x - self.px;
         let dy = to_y - self.py;
+        // Lines entirely to the left of the window only contribute cover, so
+        // collapse them to a vertical line along the left edge.
+        let left = ex1 < self.xmin && ex2 < self.xmin;
+        if left {
+            ex1 = self.xmin - 1;
+            ex2 = ex1;
+            fx1 = 0;
+            dx = 0;
+        }
         if ex1 == ex2 && ey1 == ey2 {
             // empty
         } else if dy == 0 {
@@ -371,7 +408,7 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
                 }
             }
         }
-        let fx2 = fract(to_x);
+        let fx2 = if left { 0 } else { fract(to_x) };
         let fy2 = fract(to_y);
         self.cover += fy2 - fy1;
         self.area += (fy2 - fy1) * (fx1 + fx2);
@@ -399,6 +436,20 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
             self.py = arc[0].y;
             return;
         }
+        if trunc(arc[0].x) >= self.xmax
+            && trunc(arc[1].x) >= self.xmax
+            && trunc(arc[2].x) >= self.xmax
+        {
+            self.px = arc[0].x;
+            self.py = arc[0].y;
+            return;
+        }
+        if trunc(arc[0].x) < self.xmin && trunc(arc[1].x) < self.xmin && trunc(arc[2].x) < self.xmin
+        {
+            // Only the vertical extent matters to the left of the window.
+            self.line_to(to);
+            return;
+        }
         let mut dx = (arc[2].x + arc[0].x - 2 * arc[1].x).abs();
         let dy = (arc[2].y + arc[0].y - 2 * arc[1].y).abs();
         if dx < dy {
@@ -454,6 +505,24 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
             self.py = arc[0].y;
             return;
         }
+        if trunc(arc[0].x) >= self.xmax
+            && trunc(arc[1].x) >= self.xmax
+            && trunc(arc[2].x) >= self.xmax
+            && trunc(arc[3].x) >= self.xmax
+        {
+            self.px = arc[0].x;
+            self.py = arc[0].y;
+            return;
+        }
+        if trunc(arc[0].x) < self.xmin
+            && trunc(arc[1].x) < self.xmin
+            && trunc(arc[2].x) < self.xmin
+            && trunc(arc[3].x) < self.xmin
+        {
+            // Only the vertical extent matters to the left of the window.
+            self.line_to(to);
+            return;
+        }
         let mut a = 0;
         loop {
             if (2 * arc[a].x - 3 * arc[a + 1].x + arc[a + 3].x).abs() > ONE_PIXEL / 2
@@ -484,6 +553,175 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
     }
 }
 
+impl<S: RasterStorage> Rasterizer<'_, S> {
+    /// Draws a line from the current point to the start of the subpath.
+    fn close_subpath(&mut self) {
+        if self.wide {
+            let from = self.wide_point(self.current);
+            self.current = self.first;
+            self.wide_line(from, self.wide_point(self.first));
+        } else {
+            self.line_to(self.start);
+        }
+    }
+
+    /// Converts a point in path space to pixel space relative to the window.
+    fn wide_point(&self, p: Point) -> (f64, f64) {
+        (
+            p.x as f64 + self.shift.x as f64 - self.origin.0,
+            p.y as f64 + self.shift.y as f64 - self.origin.1,
+        )
+    }
+
+    /// Returns the clip rectangle for wide mode: the window extended by a
+    /// guard band of two pixels on each side.
+    fn wide_clip(&self) -> [f64; 4] {
+        [
+            (self.xmin - 2) as f64,
+            (self.ymin - 2) as f64,
+            (self.xmax + 2) as f64,
+            (self.ymax + 2) as f64,
+        ]
+    }
+
+    /// Clips a line to the window in floating point and rasterizes the
+    /// remaining pieces. Pieces above, below or to the right of the window
+    /// are discarded and pieces to the left are collapsed to a vertical line
+    /// on the left edge of the clip rectangle since they only contribute
+    /// cover.
+    fn wide_line(&mut self, from: (f64, f64), to: (f64, f64)) {
+        let [x0, y0, x1, y1] = self.wide_clip();
+        if !(from.0.is_finite() && from.1.is_finite() && to.0.is_finite() && to.1.is_finite())
+            || (from.1 <= y0 && to.1 <= y0)
+            || (from.1 >= y1 && to.1 >= y1)
+            || (from.0 >= x1 && to.0 >= x1)
+        {
+            return;
+        }
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let mut ts = [0.; 6];
+        let mut count = 0;
+        for (edge, start, delta) in [
+            (x0, from.0, dx),
+            (x1, from.0, dx),
+            (y0, from.1, dy),
+            (y1, from.1, dy),
+        ] {
+            if delta != 0. {
+                let t = (edge - start) / delta;
+                if t > 0. && t < 1. {
+                    ts[count] = t;
+                    count += 1;
+                }
+            }
+        }
+        ts[count] = 1.;
+        let ts = &mut ts[..count + 1];
+        ts.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal));
+        let lerp = |t: f64| {
+            if t == 1. {
+                to
+            } else {
+                (from.0 + dx * t, from.1 + dy * t)
+            }
+        };
+        let mut start = from;
+        let mut t0 = 0.;
+        for &t1 in ts.iter() {
+            if t1 <= t0 {
+                continue;
+            }
+            let end = lerp(t1);
+            let mid = lerp((t0 + t1) * 0.5);
+            if mid.1 > y0 && mid.1 < y1 && mid.0 < x1 {
+                if mid.0 < x0 {
+                    self.wide_segment((x0, start.1), (x0, end.1));
+                } else {
+                    self.wide_segment(start, end);
+                }
+            }
+            start = end;
+            t0 = t1;
+        }
+    }
+
+    /// Rasterizes a line with endpoints inside the clip rectangle.
+    fn wide_segment(&mut self, from: (f64, f64), to: (f64, f64)) {
+        self.wide_move(from);
+        self.line_to(FixedPoint::from_wide(to));
+    }
+
+    /// Moves to the specified point unless the previous segment ended there.
+    fn wide_move(&mut self, to: (f64, f64)) {
+        let to = FixedPoint::from_wide(to);
+        if self.invalid || to.x != self.px || to.y != self.py {
+            self.move_to(to);
+        }
+    }
+
+    /// Rasterizes a quadratic or cubic curve, recursively subdividing it
+    /// until it either lies outside of the window or is small enough to be
+    /// flattened in fixed point.
+    fn wide_curve(&mut self, points: &[(f64, f64)], depth: u32) {
+        let [x0, y0, x1, y1] = self.wide_clip();
+        let mut min = (f64::INFINITY, f64::INFINITY);
+        let mut max = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for p in points {
+            if !(p.0.is_finite() && p.1.is_finite()) {
+                return;
+            }
+            min = (min.0.min(p.0), min.1.min(p.1));
+            max = (max.0.max(p.0), max.1.max(p.1));
+        }
+        let first = points[0];
+        let last = points[points.len() - 1];
+        if max.1 <= y0 || min.1 >= y1 || min.0 >= x1 {
+            return;
+        }
+        if max.0 <= x0 || depth >= MAX_WIDE_DEPTH {
+            self.wide_line(first, last);
+            return;
+        }
+        let margin = MAX_WIDE_DIM as f64;
+        if min.0 >= x0 - margin
+            && min.1 >= y0 - margin
+            && max.0 <= x1 + margin
+            && max.1 <= y1 + margin
+        {
+            self.wide_move(first);
+            let to = FixedPoint::from_wide(last);
+            if points.len() == 3 {
+                self.quad_to(FixedPoint::from_wide(points[1]), to);
+            } else {
+                self.curve_to(
+                    FixedPoint::from_wide(points[1]),
+                    FixedPoint::from_wide(points[2]),
+                    to,
+                );
+            }
+            return;
+        }
+        // Split at the midpoint with de Casteljau's algorithm.
+        let mid = |a: (f64, f64), b: (f64, f64)| ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5);
+        if points.len() == 3 {
+            let (a, b) = (mid(points[0], points[1]), mid(points[1], points[2]));
+            let c = mid(a, b);
+            self.wide_curve(&[points[0], a, c], depth + 1);
+            self.wide_curve(&[c, b, points[2]], depth + 1);
+        } else {
+            let (a, b, c) = (
+                mid(points[0], points[1]),
+                mid(points[1], points[2]),
+                mid(points[2], points[3]),
+            );
+            let (d, e) = (mid(a, b), mid(b, c));
+            let f = mid(d, e);
+            self.wide_curve(&[points[0], a, d, f], depth + 1);
+            self.wide_curve(&[f, e, c, points[3]], depth + 1);
+        }
+    }
+}
+
 impl<S: RasterStorage> PathBuilder for Rasterizer<'_, S> {
     fn current_point(&self) -> Point {
         self.current + self.shift
@@ -492,20 +730,31 @@ impl<S: RasterStorage> PathBuilder for Rasterizer<'_, S> {
     #[inline(always)]
     fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
         if !self.closed {
-            self.line_to(self.start);
+            self.close_subpath();
         }
         let to = to.into();
+        self.closed = false;
+        self.current = to;
+        if self.wide {
+            self.first = to;
+            return self;
+        }
         let p = FixedPoint::from_point(to + self.shift);
         self.move_to(p);
-        self.closed = false;
         self.start = p;
-        self.current = to;
         self
     }
 
     #[inline(always)]
     fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
         let to = to.into();
+        if self.wide {
+            let from = self.wide_point(self.current);
+            self.current = to;
+            self.closed = false;
+            self.wide_line(from, self.wide_point(to));
+            return self;
+        }
         self.current = to;
         self.closed = false;
         self.line_to(FixedPoint::from_point(to + self.shift));
@@ -515,6 +764,14 @@ impl<S: RasterStorage> PathBuilder for Rasterizer<'_, S> {
     #[inline(always)]
     fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
         let to = to.into();
+        if self.wide {
+            let from = self.wide_point(self.current);
+            self.current = to;
+            self.closed = false;
+            let control = self.wide_point(control.into());
+            self.wide_curve(&[from, control, self.wide_point(to)], 0);
+            return self;
+        }
         self.current = to;
         self.closed = false;
         self.quad_to(
@@ -532,6 +789,15 @@ impl<S: RasterStorage> PathBuilder for Rasterizer<'_, S> {
         to: impl Into<Point>,
     ) -> &mut Self {
         let to = to.into();
+        if self.wide {
+            let from = self.wide_point(self.current);
+            self.current = to;
+            self.closed = false;
+            let control1 = self.wide_point(control1.into());
+            let control2 = self.wide_point(control2.into());
+            self.wide_curve(&[from, control1, control2, self.wide_point(to)], 0);
+            return self;
+        }
         self.current = to;
         self.closed = false;
         self.curve_to(
@@ -544,12 +810,75 @@ impl<S: RasterStorage> PathBuilder for Rasterizer<'_, S> {
 
     #[inline(always)]
     fn close(&mut self) -> &mut Self {
-        self.line_to(self.start);
+        self.close_subpath();
         self.closed = true;
         self
     }
 }
 
+/// Path builder that forwards commands to a set of rasterizers, allowing
+/// multiple passes with different shifts to share a single application of
+/// the path style.
+pub struct Rasterizers<'r, 'a, S: RasterStorage>(pub &'r mut [Rasterizer<'a, S>]);
+
+impl<S: RasterStorage> PathBuilder for Rasterizers<'_, '_, S> {
+    fn current_point(&self) -> Point {
+        self.0.first().map(|r| r.current).unwrap_or_default()
+    }
+
+    #[inline(always)]
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        for r in self.0.iter_mut() {
+            PathBuilder::move_to(r, to);
+        }
+        self
+    }
+
+    #[inline(always)]
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        for r in self.0.iter_mut() {
+            PathBuilder::line_to(r, to);
+        }
+        self
+    }
+
+    #[inline(always)]
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        let control = control.into();
+        let to = to.into();
+        for r in self.0.iter_mut() {
+            PathBuilder::quad_to(r, control, to);
+        }
+        self
+    }
+
+    #[inline(always)]
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let control1 = control1.into();
+        let control2 = control2.into();
+        let to = to.into();
+        for r in self.0.iter_mut() {
+            PathBuilder::curve_to(r, control1, control2, to);
+        }
+        self
+    }
+
+    #[inline(always)]
+    fn close(&mut self) -> &mut Self {
+        for r in self.0.iter_mut() {
+            PathBuilder::close(r);
+        }
+        self
+    }
+}
+
 #[derive(Copy, Clone, Default)]
 pub struct Cell {
     x: i32,
@@ -558,6 +887,52 @@ pub struct Cell {
     next: i32,
 }
 
+/// Most recently accessed cell in a row along with its predecessor, or -1
+/// if the predecessor is unknown or the cell is at the head of the row.
+///
+/// Cells are produced by walking along lines, so successive accesses to a
+/// row are usually adjacent and the search can begin at the cursor rather
+/// than at the head of the row.
+#[derive(Copy, Clone)]
+struct Cursor {
+    prev: i32,
+    cell: i32,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self { prev: -1, cell: -1 }
+    }
+}
+
+/// Searches the row of cells beginning at the specified head for the cell at
+/// x, starting at the cursor where possible. Returns the index of the
+/// predecessor and the index of the cell at x if it exists, or otherwise the
+/// indices of the cells between which a new cell at x belongs. The cursor is
+/// updated to refer to the result.
+#[inline(always)]
+fn find_cell(cells: &[Cell], head: i32, cursor: &mut Cursor, x: i32) -> (i32, i32) {
+    let mut last_index = -1;
+    let mut cell_index = head;
+    if cursor.cell != -1 && cells[cursor.cell as usize].x <= x {
+        last_index = cursor.prev;
+        cell_index = cursor.cell;
+    } else if cursor.prev != -1 && cells[cursor.prev as usize].x <= x {
+        cell_index = cursor.prev;
+    }
+    while cell_index != -1 {
+        let cell = &cells[cell_index as usize];
+        if cell.x >= x {
+            break;
+        }
+        last_index = cell_index;
+        cell_index = cell.next;
+    }
+    cursor.prev = last_index;
+    cursor.cell = cell_index;
+    (last_index, cell_index)
+}
+
 pub trait RasterStorage {
     fn reset(&mut self, min: FixedPoint, max: FixedPoint);
     fn cells(&self) -> &[Cell];
@@ -571,6 +946,7 @@ pub struct HeapStorage {
     max: FixedPoint,
     cells: Vec<Cell>,
     indices: Vec<i32>,
+    cursors: Vec<Cursor>,
 }
 
 impl RasterStorage for HeapStorage {
@@ -580,6 +956,9 @@ impl RasterStorage for HeapStorage {
         self.cells.clear();
         self.indices.clear();
         self.indices.resize((max.y - min.y) as usize, -1);
+        self.cursors.clear();
+        self.cursors
+            .resize((max.y - min.y) as usize, Cursor::default());
     }
 
     fn cells(&self) -> &[Cell] {
@@ -591,22 +970,15 @@ impl RasterStorage for HeapStorage {
     }
 
     #[inline(always)]
-    #[allow(clippy::comparison_chain)]
     fn set(&mut self, x: i32, y: i32, area: i32, cover: i32) {
         let yindex = (y - self.min.y) as usize;
-        let mut cell_index = self.indices[yindex];
-        let mut last_index = -1;
-        while cell_index != -1 {
+        let cursor = &mut self.cursors[yindex];
+        let (last_index, cell_index) = find_cell(&self.cells, self.indices[yindex], cursor, x);
+        if cell_index != -1 && self.cells[cell_index as usize].x == x {
             let cell = &mut self.cells[cell_index as usize];
-            if cell.x > x {
-                break;
-            } else if cell.x == x {
-                cell.area = cell.area.wrapping_add(area);
-                cell.cover = cell.cover.wrapping_add(cover);
-                return;
-            }
-            last_index = cell_index;
-            cell_index = cell.next;
+            cell.area = cell.area.wrapping_add(area);
+            cell.cover = cell.cover.wrapping_add(cover);
+            return;
         }
         let new_index = self.cells.len();
         let cell = Cell {
@@ -620,6 +992,7 @@ impl RasterStorage for HeapStorage {
         } else {
             self.indices[yindex] = new_index as i32;
         }
+        cursor.cell = new_index as i32;
         self.cells.push(cell);
     }
 }
@@ -636,6 +1009,8 @@ pub struct AdaptiveStorage {
     heap_cells: Vec<Cell>,
     indices: [i32; MAX_BAND],
     heap_indices: Vec<i32>,
+    cursors: [Cursor; MAX_BAND],
+    heap_cursors: Vec<Cursor>,
 }
 
 impl AdaptiveStorage {
@@ -650,6 +1025,8 @@ impl AdaptiveStorage {
             heap_cells: Vec::new(),
             indices: [Default::default(); MAX_BAND],
             heap_indices: Vec::new(),
+            cursors: [Default::default(); MAX_BAND],
+            heap_cursors: Vec::new(),
         }
     }
 }
@@ -662,12 +1039,13 @@ impl RasterStorage for AdaptiveStorage {
         self.cell_count = 0;
         self.heap_cells.clear();
         self.heap_indices.clear();
+        self.heap_cursors.clear();
         if self.height > MAX_BAND {
-            self.heap_indices.resize((max.y - min.y) as usize, -1);
+            self.heap_indices.resize(self.height, -1);
+            self.heap_cursors.resize(self.height, Cursor::default());
         } else {
-            for i in 0..self.height {
-                self.indices[i] = -1;
-            }
+            self.indices[..self.height].fill(-1);
+            self.cursors[..self.height].fill(Cursor::default());
         }
     }
 
@@ -688,34 +1066,28 @@ impl RasterStorage for AdaptiveStorage {
     }
 
     #[inline(always)]
-    #[allow(clippy::comparison_chain)]
     fn set(&mut self, x: i32, y: i32, area: i32, cover: i32) {
         let yindex = (y - self.min.y) as usize;
-        let indices = if self.height > MAX_BAND {
-            &mut self.heap_indices[..]
+        let (indices, cursors) = if self.height > MAX_BAND {
+            (&mut self.heap_indices[..], &mut self.heap_cursors[..])
         } else {
-            &mut self.indices[..]
+            (&mut self.indices[..], &mut self.cursors[..])
         };
         let cells = if !self.heap_cells.is_empty() {
             &mut self.heap_cells[..]
         } else {
             &mut self.cells[..]
         };
-        let mut cell_index = indices[yindex];
-        let mut last_index = -1;
-        while cell_index != -1 {
+        let cursor = &mut cursors[yindex];
+        let (last_index, cell_index) = find_cell(cells, indices[yindex], cursor, x);
+        if cell_index != -1 && cells[cell_index as usize].x == x {
             let cell = &mut cells[cell_index as usize];
-            if cell.x > x {
-                break;
-            } else if cell.x == x {
-                cell.area = cell.area.wrapping_add(area);
-                cell.cover = cell.cover.wrapping_add(cover);
-                return;
-            }
-            last_index = cell_index;
-            cell_index = cell.next;
+            cell.area = cell.area.wrapping_add(area);
+            cell.cover = cell.cover.wrapping_add(cover);
+            return;
         }
         let new_index = self.cell_count;
+        cursor.cell = new_index as i32;
         self.cell_count += 1;
         let cell = Cell {
             x,
@@ -739,7 +1111,21 @@ impl RasterStorage for AdaptiveStorage {
     }
 }
 
-const _MAX_DIM: u32 = i16::MAX as u32;
+/// Maximum window dimension supported by the fixed point line setup.
+pub const MAX_DIM: u32 = i16::MAX as u32;
+
+/// Maximum window dimension supported in wide mode. Curves are converted to
+/// fixed point once they lie within this distance of the window, so
+/// coordinates may span three times this range without overflowing the
+/// fixed point subdivision.
+pub const MAX_WIDE_DIM: u32 = 1 << 18;
+
+/// Maximum horizontal or vertical extent of a line in wide mode, in fixed
+/// point units.
+const MAX_WIDE_LINE: i32 = 256 << PIXEL_BITS;
+
+/// Maximum subdivision depth for curves in wide mode.
+const MAX_WIDE_DEPTH: u32 = 128;
 
 fn split_quad(base: &mut [FixedPoint]) {
     let mut a;
@@ -803,6 +1189,13 @@ impl FixedPoint {
         Self { x, y }
     }
 
+    fn from_wide(p: (f64, f64)) -> Self {
+        Self {
+            x: (p.0 * 256.) as i32,
+            y: (p.1 * 256.) as i32,
+        }
+    }
+
     #[inline(always)]
     pub fn from_point(p: Point) -> Self {
         Self {
@@ -0,0 +1,202 @@
+//! Aliased rasterization by sampling pixel centers.
+
+use super::command::Command;
+use super::geometry::{Point, Vector};
+use super::sdf::{crossing, flatten, inside};
+use super::segment::{segments, Curve, Line, Segment};
+use super::style::Fill;
+#[allow(unused)]
+use super::F32Ext;
+
+use crate::lib::Vec;
+use core::cmp::Ordering;
+
+/// Path geometry flattened into a set of closed polygons for sampling the
+/// fill at pixel centers.
+pub struct Sampler {
+    /// Non-horizontal lines of the polygons ordered by minimum vertical
+    /// coordinate.
+    lines: Vec<Line>,
+    fill: Fill,
+}
+
+impl Sampler {
+    /// Creates a new sampler from a sequence of commands, translating each
+    /// point by the specified shift. Open subpaths are implicitly closed.
+    pub fn new(commands: &[Command], shift: Vector, fill: Fill) -> Self {
+        let mut lines = Vec::new();
+        polygons(commands, shift, &mut |line, _| lines.push(line));
+        lines.retain(|line| line.a.y != line.b.y);
+        lines.sort_unstable_by(|a, b| {
+            let (a, b) = (a.a.y.min(a.b.y), b.a.y.min(b.b.y));
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        });
+        Self { lines, fill }
+    }
+
+    /// Invokes the closure with the row, column, length and 16-bit coverage
+    /// of each span of pixels in the window with the specified minimum
+    /// corner and size. Pixels are fully covered if their centers are inside
+    /// the path. Centers that lie exactly on an edge are inside if the edge
+    /// is a left or top edge. When dense is true, spans of uncovered pixels
+    /// are also emitted.
+    pub fn sample(
+        &self,
+        min: (i32, i32),
+        size: (usize, usize),
+        y_up: bool,
+        dense: bool,
+        write: &mut impl FnMut(usize, usize, usize, u16),
+    ) {
+        let width = size.0;
+        self.rows(min, size, y_up, &mut |y, crossings| {
+            let mut x = 0;
+            let mut winding = 0;
+            let mut start = None;
+            for &(column, dir) in crossings {
+                winding += dir;
+                let is_inside = inside(self.fill, winding);
+                match start {
+                    None if is_inside => start = Some(column),
+                    Some(first) if !is_inside => {
+                        if column > first {
+                            if dense && first > x {
+                                write(y, x, first - x, 0);
+                            }
+                            write(y, first, column - first, 65535);
+                            x = column;
+                        }
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(first) = start {
+                if width > first {
+                    if dense && first > x {
+                        write(y, x, first - x, 0);
+                    }
+                    write(y, first, width - first, 65535);
+                    x = width;
+                }
+            }
+            if dense && width > x {
+                write(y, x, width - x, 0);
+            }
+        });
+    }
+
+    /// Invokes the closure with the row, column, length and winding number
+    /// of each span of pixels in the window with the specified minimum
+    /// corner and size that have the same non-zero winding number at their
+    /// centers. Centers that lie exactly on an edge are sampled in the same
+    /// manner as [`Self::sample`].
+    pub fn winding(
+        &self,
+        min: (i32, i32),
+        size: (usize, usize),
+        y_up: bool,
+        write: &mut impl FnMut(usize, usize, usize, i32),
+    ) {
+        let width = size.0;
+        self.rows(min, size, y_up, &mut |y, crossings| {
+            let mut x = 0;
+            let mut winding = 0;
+            for &(column, dir) in crossings {
+                if winding != 0 && column > x {
+                    write(y, x, column - x, winding);
+                }
+                winding += dir;
+                x = column;
+            }
+            if winding != 0 && width > x {
+                write(y, x, width - x, winding);
+            }
+        });
+    }
+
+    /// Invokes the closure with the row and the crossings of the path with
+    /// the pixel centers of each row of the window, ordered by column. Each
+    /// crossing is the first column with a center at or to the right of the
+    /// edge, along with the direction of the edge.
+    fn rows(
+        &self,
+        min: (i32, i32),
+        size: (usize, usize),
+        y_up: bool,
+        f: &mut impl FnMut(usize, &[(usize, i32)]),
+    ) {
+        let (width, height) = size;
+        let mut active: Vec<&Line> = Vec::new();
+        let mut crossings = Vec::new();
+        let mut columns = Vec::new();
+        let mut next = 0;
+        for row in 0..height {
+            let cy = (min.1 + row as i32) as f32 + 0.5;
+            let y = if y_up { height - 1 - row } else { row };
+            while let Some(line) = self.lines.get(next) {
+                if line.a.y.min(line.b.y) > cy {
+                    break;
+                }
+                active.push(line);
+                next += 1;
+            }
+            active.retain(|line| line.a.y.max(line.b.y) > cy);
+            crossings.clear();
+            crossings.extend(active.iter().filter_map(|line| crossing(line, cy)));
+            crossings.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+            columns.clear();
+            columns.extend(crossings.iter().map(|&(cx, dir)| {
+                let column = (cx - 0.5 - min.0 as f32).ceil().clamp(0., width as f32) as usize;
+                (column, dir)
+            }));
+            f(y, &columns);
+        }
+    }
+}
+
+/// Flattens a sequence of commands into closed polygons, translating each
+/// point by the specified shift and invoking the closure with each line and
+/// whether it approximates a curve. Open subpaths are implicitly closed.
+pub(super) fn polygons(commands: &[Command], shift: Vector, f: &mut impl FnMut(Line, bool)) {
+    let mut lines = Vec::new();
+    let mut start = None;
+    let mut last = Point::ZERO;
+    for segment in segments(commands.iter(), false) {
+        match segment {
+            Segment::Line(_, line) => {
+                let line = Line::new(line.a + shift, line.b + shift);
+                start.get_or_insert(line.a);
+                f(line, false);
+                last = line.b;
+            }
+            Segment::Curve(_, curve) => {
+                let curve = Curve::new(
+                    curve.a + shift,
+                    curve.b + shift,
+                    curve.c + shift,
+                    curve.d + shift,
+                );
+                start.get_or_insert(curve.a);
+                lines.clear();
+                flatten(&curve, &mut lines);
+                for line in &lines {
+                    f(*line, true);
+                }
+                last = curve.d;
+            }
+            Segment::End(_) => {
+                if let Some(start) = start.take() {
+                    if last != start {
+                        f(Line::new(last, start), false);
+                    }
+                }
+            }
+        }
+    }
+    if let Some(start) = start {
+        if last != start {
+            f(Line::new(last, start), false);
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Path data sanitization.
+
+use super::command::Command;
+use super::error::Error;
+use super::geometry::Point;
+use super::path_builder::PathBuilder;
+use super::path_data::PathData;
+
+/// Magnitude to which infinite coordinates are clamped. This is the largest
+/// power of two below which all integers are exactly representable.
+const CLAMP_LIMIT: f32 = 16777216.;
+
+/// Policy for handling path data with infinite or NaN coordinates.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Sanitize {
+    /// Removes each subpath that contains a non-finite coordinate.
+    SkipSubpath,
+    /// Replaces infinite coordinates with finite values of the same sign and
+    /// large magnitude, and NaN coordinates with the corresponding
+    /// coordinate of the previous point. Finite coordinates are unchanged.
+    ///
+    /// Since the replaced coordinates lie far outside of the range of the
+    /// default rasterizer, masks with this policy are always rendered in
+    /// [wide mode](super::Mask::wide).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zeno::{Command, Mask, PathBuilder, Sanitize};
+    ///
+    /// let inf = f32::INFINITY;
+    /// let mut path: Vec<Command> = Vec::new();
+    /// path.move_to([-inf, -inf])
+    ///     .line_to([inf, -inf])
+    ///     .line_to([f32::NAN, inf])
+    ///     .line_to([-inf, f32::NAN])
+    ///     .close();
+    ///
+    /// let (mask, _) = Mask::new(&path)
+    ///     .size(4, 4)
+    ///     .sanitize(Sanitize::Clamp)
+    ///     .render();
+    /// assert!(mask.iter().all(|&coverage| coverage == 255));
+    ///
+    /// let (checked, _) = Mask::new(&path)
+    ///     .size(4, 4)
+    ///     .sanitize(Sanitize::Clamp)
+    ///     .try_render()
+    ///     .unwrap();
+    /// assert_eq!(checked, mask);
+    /// ```
+    Clamp,
+    /// Rejects the entire path. The path is treated as empty and the
+    /// fallible render functions return [`Error::NonFiniteGeometry`].
+    Error,
+}
+
+/// Path data adapter that removes degenerate segments and handles non-finite
+/// coordinates according to a policy.
+///
+/// Regardless of the policy, line segments of zero length and curves with
+/// all points coincident are removed, and curves with control points that
+/// coincide with their end points are replaced with lines. Since this is
+/// implemented as path data, the same sanitized path is observed by filling,
+/// stroking, bounds computation and traversal.
+///
+/// # Example
+///
+/// ```rust
+/// use zeno::{bounds, Command, Fill, PathBuilder, PathData, Sanitize, Sanitized};
+///
+/// let mut path: Vec<Command> = Vec::new();
+/// path.move_to([0.0, 0.0]).line_to([10.0, 0.0]).line_to([10.0, 10.0]).close();
+/// path.move_to([20.0, 0.0]).line_to([f32::NAN, 5.0]).close();
+///
+/// let sanitized = Sanitized::new(&path, Sanitize::SkipSubpath);
+/// assert_eq!(sanitized.commands().count(), 4);
+///
+/// let bounds = bounds(sanitized, Fill::NonZero, None);
+/// assert_eq!(bounds.max.x, 10.0);
+///
+/// let rejected = Sanitized::new(&path, Sanitize::Error);
+/// assert!(rejected.check().is_err());
+/// assert_eq!(rejected.commands().count(), 0);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Sanitized<D> {
+    data: D,
+    policy: Option<Sanitize>,
+}
+
+impl<D: PathData> Sanitized<D> {
+    /// Creates a new adapter for the specified path data and policy.
+    pub fn new(data: D, policy: Sanitize) -> Self {
+        Self {
+            data,
+            policy: Some(policy),
+        }
+    }
+
+    /// Creates an adapter that passes the path data through unchanged when
+    /// no policy is specified.
+    #[cfg(feature = "eval")]
+    pub(crate) fn with_policy(data: D, policy: Option<Sanitize>) -> Self {
+        Self { data, policy }
+    }
+
+    /// Returns an error if the policy rejects the path data.
+    pub fn check(&self) -> Result<(), Error> {
+        if self.policy == Some(Sanitize::Error) && !is_finite(self.data.commands()) {
+            Err(Error::NonFiniteGeometry)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<D: PathData> PathData for Sanitized<D> {
+    type Commands = SanitizedCommands<D::Commands>;
+
+    fn commands(&self) -> Self::Commands {
+        let commands = self.data.commands();
+        let (skip, done) = match self.policy {
+            Some(Sanitize::SkipSubpath) => (!is_finite(subpath(commands.clone())), false),
+            Some(Sanitize::Error) => (false, !is_finite(commands.clone())),
+            _ => (false, false),
+        };
+        SanitizedCommands {
+            commands,
+            policy: self.policy,
+            start: Point::ZERO,
+            current: Point::ZERO,
+            skip,
+            done,
+        }
+    }
+
+    #[inline(always)]
+    fn copy_to(&self, sink: &mut impl PathBuilder) {
+        if self.policy.is_none() {
+            self.data.copy_to(sink);
+        } else {
+            self.commands().copy_to(sink);
+        }
+    }
+}
+
+/// Iterator over sanitized path commands.
+#[derive(Clone)]
+pub struct SanitizedCommands<I> {
+    commands: I,
+    policy: Option<Sanitize>,
+    start: Point,
+    current: Point,
+    skip: bool,
+    done: bool,
+}
+
+impl<I> Iterator for SanitizedCommands<I>
+where
+    I: Iterator<Item = Command> + Clone,
+{
+    type Item = Command;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use Command::*;
+        let Some(policy) = self.policy else {
+            return self.commands.next();
+        };
+        if self.done {
+            return None;
+        }
+        loop {
+            let mut cmd = self.commands.next()?;
+            if policy == Sanitize::SkipSubpath {
+                if let MoveTo(_) = cmd {
+                    self.skip = !is_finite(subpath(self.commands.clone()).chain(Some(cmd)));
+                }
+                if self.skip {
+                    continue;
+                }
+            } else if policy == Sanitize::Clamp {
+                cmd = clamp(cmd, self.current);
+            }
+            let from = self.current;
+            match cmd {
+                MoveTo(p) => {
+                    self.start = p;
+                    self.current = p;
+                }
+                LineTo(p) => {
+                    if p == from {
+                        continue;
+                    }
+                    self.current = p;
+                }
+                QuadTo(c, p) => {
+                    if c == from && p == from {
+                        continue;
+                    }
+                    self.current = p;
+                    if c == from || c == p {
+                        return Some(LineTo(p));
+                    }
+                }
+                CurveTo(c1, c2, p) => {
+                    if c1 == from && c2 == from && p == from {
+                        continue;
+                    }
+                    self.current = p;
+                    if (c1 == from || c1 == p) && (c2 == from || c2 == p) {
+                        return Some(LineTo(p));
+                    }
+                }
+                Close => {
+                    self.current = self.start;
+                }
+            }
+            return Some(cmd);
+        }
+    }
+}
+
+impl<I> SanitizedCommands<I>
+where
+    I: Iterator<Item = Command> + Clone,
+{
+    fn copy_to(self, sink: &mut impl PathBuilder) {
+        for cmd in self {
+            use Command::*;
+            match cmd {
+                MoveTo(p) => sink.move_to(p),
+                LineTo(p) => sink.line_to(p),
+                QuadTo(c, p) => sink.quad_to(c, p),
+                CurveTo(c1, c2, p) => sink.curve_to(c1, c2, p),
+                Close => sink.close(),
+            };
+        }
+    }
+}
+
+/// Returns the commands up to, but not including, the next move.
+fn subpath(commands: impl Iterator<Item = Command>) -> impl Iterator<Item = Command> {
+    commands.take_while(|cmd| !matches!(cmd, Command::MoveTo(_)))
+}
+
+/// Returns true if all coordinates of the commands are finite.
+pub(crate) fn is_finite(mut commands: impl Iterator<Item = Command>) -> bool {
+    let finite = |p: Point| p.x.is_finite() && p.y.is_finite();
+    commands.all(|cmd| match cmd {
+        Command::MoveTo(p) | Command::LineTo(p) => finite(p),
+        Command::QuadTo(c, p) => finite(c) && finite(p),
+        Command::CurveTo(c1, c2, p) => finite(c1) && finite(c2) && finite(p),
+        Command::Close => true,
+    })
+}
+
+/// Replaces the non-finite coordinates of a command.
+fn clamp(cmd: Command, prev: Point) -> Command {
+    let clamp = |p: Point| {
+        let clamp = |v: f32, prev: f32| {
+            if v.is_nan() {
+                prev
+            } else if v.is_infinite() {
+                if v > 0. {
+                    CLAMP_LIMIT
+                } else {
+                    -CLAMP_LIMIT
+                }
+            } else {
+                v
+            }
+        };
+        Point::new(clamp(p.x, prev.x), clamp(p.y, prev.y))
+    };
+    match cmd {
+        Command::MoveTo(p) => Command::MoveTo(clamp(p)),
+        Command::LineTo(p) => Command::LineTo(clamp(p)),
+        Command::QuadTo(c, p) => Command::QuadTo(clamp(c), clamp(p)),
+        Command::CurveTo(c1, c2, p) => Command::CurveTo(clamp(c1), clamp(c2), clamp(p)),
+        Command::Close => Command::Close,
+    }
+}
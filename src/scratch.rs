@@ -16,6 +16,7 @@ use core::borrow::Borrow;
 pub struct Scratch {
     pub(super) inner: Inner,
     pub(super) render: HeapStorage,
+    pub(super) subpixel: [HeapStorage; 2],
 }
 
 impl Scratch {
@@ -47,6 +48,20 @@ impl Scratch {
         self.apply(data, style, transform, &mut bounds);
         bounds.build()
     }
+
+    /// Computes a conservative bounding box of the path that includes the
+    /// control points of curves.
+    pub fn loose_bounds<'a>(
+        &mut self,
+        data: impl PathData,
+        style: impl Into<Style<'a>>,
+        transform: Option<Transform>,
+    ) -> Bounds {
+        let style = style.into();
+        let mut bounds = BoundsBuilder::loose();
+        self.apply(data, style, transform, &mut bounds);
+        bounds.build()
+    }
 }
 
 #[derive(Default)]
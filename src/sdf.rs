@@ -0,0 +1,523 @@
+//! Signed distance field generation.
+
+use super::command::Command;
+use super::geometry::{Point, Vector};
+use super::segment::{segments, Curve, Line, Segment};
+use super::style::Fill;
+#[allow(unused)]
+use super::F32Ext;
+
+use crate::lib::Vec;
+use core::cmp::Ordering;
+
+/// Maximum distance between a flattened curve and the curve itself in pixels.
+pub(super) const TOLERANCE: f32 = 0.05;
+
+/// Sine of the minimum angle between two segments that is considered a
+/// corner for edge coloring.
+const CORNER_THRESHOLD: f32 = 0.14112;
+
+const RED: u8 = 1;
+const GREEN: u8 = 2;
+const BLUE: u8 = 4;
+const WHITE: u8 = RED | GREEN | BLUE;
+const COLORS: [u8; 3] = [GREEN | BLUE, RED | BLUE, RED | GREEN];
+
+/// Portion of the outline that separates the inside of the path from the
+/// outside, oriented such that the inside lies in the direction of the
+/// normal `(-dy, dx)`.
+#[derive(Copy, Clone)]
+struct Edge {
+    line: Line,
+    /// Channels of a multi-channel field that the edge contributes to.
+    color: u8,
+    /// True if the start and end points of the edge are corners, in which
+    /// case the pseudo-distance extends beyond them.
+    corners: (bool, bool),
+}
+
+/// Path geometry flattened into a set of closed polygons for evaluating
+/// signed distances.
+pub struct Field {
+    /// Lines of the polygons, used to determine the winding number.
+    lines: Vec<Line>,
+    /// Portions of the lines that separate the inside of the path from the
+    /// outside, used to determine the distance.
+    edges: Vec<Edge>,
+    fill: Fill,
+}
+
+impl Field {
+    /// Creates a new field from a sequence of commands, translating each
+    /// point by the specified shift. Open subpaths are implicitly closed.
+    pub fn new(commands: &[Command], shift: Vector, fill: Fill) -> Self {
+        let mut lines = Vec::new();
+        // True for lines that continue the curve of the previous line.
+        let mut smooth = Vec::new();
+        let mut contours = Vec::new();
+        let mut start = None;
+        let mut last = Point::ZERO;
+        for segment in segments(commands.iter(), false) {
+            match segment {
+                Segment::Line(_, line) => {
+                    let line = Line::new(line.a + shift, line.b + shift);
+                    start.get_or_insert(line.a);
+                    lines.push(line);
+                    smooth.push(false);
+                    last = line.b;
+                }
+                Segment::Curve(_, curve) => {
+                    let curve = Curve::new(
+                        curve.a + shift,
+                        curve.b + shift,
+                        curve.c + shift,
+                        curve.d + shift,
+                    );
+                    start.get_or_insert(curve.a);
+                    let first = lines.len();
+                    flatten(&curve, &mut lines);
+                    smooth.resize(lines.len(), true);
+                    smooth[first] = false;
+                    last = curve.d;
+                }
+                Segment::End(_) => {
+                    if let Some(start) = start.take() {
+                        if last != start {
+                            lines.push(Line::new(last, start));
+                            smooth.push(false);
+                        }
+                        contours.push(lines.len());
+                    }
+                }
+            }
+        }
+        if let Some(start) = start {
+            if last != start {
+                lines.push(Line::new(last, start));
+                smooth.push(false);
+            }
+            contours.push(lines.len());
+        }
+        let mut field = Self {
+            lines,
+            edges: Vec::new(),
+            fill,
+        };
+        let (colors, corners) = field.color_lines(&contours, &smooth);
+        field.edges = field.boundary(&colors, &corners);
+        field
+    }
+
+    /// Invokes the closure with the column, row and signed distances of each
+    /// pixel center in the window with the specified minimum corner and size.
+    /// Distances are positive inside the path and clamped to the range
+    /// `-spread..=spread`.
+    ///
+    /// The last element of the distances is the true signed distance. When
+    /// multi is true, the first three elements are the red, green and blue
+    /// channels of a multi-channel field. Otherwise, they are unspecified.
+    pub fn evaluate(
+        &self,
+        min: (i32, i32),
+        size: (usize, usize),
+        spread: f32,
+        multi: bool,
+        write: &mut impl FnMut(usize, usize, [f32; 4]),
+    ) {
+        let (width, height) = size;
+        let mut crossings = Vec::new();
+        let mut candidates = Vec::new();
+        for y in 0..height {
+            let cy = (min.1 + y as i32) as f32 + 0.5;
+            crossings.clear();
+            candidates.clear();
+            for line in &self.lines {
+                if let Some(crossing) = crossing(line, cy) {
+                    crossings.push(crossing);
+                }
+            }
+            for edge in &self.edges {
+                let line = &edge.line;
+                if line.a.y.min(line.b.y) - spread <= cy && line.a.y.max(line.b.y) + spread >= cy {
+                    candidates.push(*edge);
+                }
+            }
+            crossings.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+            let mut winding = 0;
+            let mut next = 0;
+            for x in 0..width {
+                let cx = (min.0 + x as i32) as f32 + 0.5;
+                while next < crossings.len() && crossings[next].0 < cx {
+                    winding += crossings[next].1;
+                    next += 1;
+                }
+                let point = Point::new(cx, cy);
+                let is_inside = inside(self.fill, winding);
+                let mut distance = spread;
+                // Distance to the nearest edge of each channel.
+                let mut nearest: [(f32, Option<&Edge>); 3] = [(spread, None); 3];
+                for edge in &candidates {
+                    let line = &edge.line;
+                    let limit = if multi {
+                        nearest[0].0.max(nearest[1].0).max(nearest[2].0)
+                    } else {
+                        distance
+                    };
+                    if line.a.x.min(line.b.x) - limit > cx || line.a.x.max(line.b.x) + limit < cx {
+                        continue;
+                    }
+                    let d = distance_to_line(line, point);
+                    distance = distance.min(d);
+                    if multi {
+                        for (channel, nearest) in nearest.iter_mut().enumerate() {
+                            if edge.color & (1 << channel) != 0 && d < nearest.0 {
+                                *nearest = (d, Some(edge));
+                            }
+                        }
+                    }
+                }
+                if !is_inside {
+                    distance = -distance;
+                }
+                let mut distances = [distance; 4];
+                if multi {
+                    for (channel, nearest) in nearest.iter().enumerate() {
+                        distances[channel] = match nearest.1 {
+                            Some(edge) => pseudo_distance(edge, point).clamp(-spread, spread),
+                            _ => -spread,
+                        };
+                    }
+                    // Fall back to the true distance where the median of the
+                    // channels disagrees with the inside test.
+                    if (median(distances[0], distances[1], distances[2]) > 0.) != is_inside {
+                        distances = [distance; 4];
+                    }
+                }
+                write(x, y, distances);
+            }
+        }
+    }
+
+    /// Assigns channels to the lines of each contour such that the lines
+    /// that meet at a corner share only one channel. Returns the colors of
+    /// the lines along with flags indicating whether their start and end
+    /// points are corners.
+    fn color_lines(&self, contours: &[usize], smooth: &[bool]) -> (Vec<u8>, Vec<(bool, bool)>) {
+        let lines = &self.lines;
+        let mut colors = Vec::with_capacity(lines.len());
+        let mut corners = Vec::with_capacity(lines.len());
+        let mut splines = Vec::new();
+        let mut start = 0;
+        for &end in contours {
+            let count = end - start;
+            let next = |i: usize| if i + 1 == end { start } else { i + 1 };
+            // Indices of the lines that begin at a corner.
+            splines.clear();
+            for i in start..end {
+                let prev = &lines[if i == start { end - 1 } else { i - 1 }];
+                if !smooth[i] && is_corner(prev, &lines[i]) {
+                    splines.push(i);
+                }
+            }
+            for i in start..end {
+                corners.push((splines.contains(&i), splines.contains(&next(i))));
+            }
+            colors.resize(end, WHITE);
+            match splines.len() {
+                0 => {}
+                1 => {
+                    // Split a contour with a single corner into thirds.
+                    for j in 0..count {
+                        let i = start + (splines[0] - start + j) % count;
+                        colors[i] = COLORS[j * 3 / count];
+                    }
+                }
+                n => {
+                    let mut color = 0;
+                    for (k, &first) in splines.iter().enumerate() {
+                        if k != 0 {
+                            color = (color + 1) % 3;
+                            if k == n - 1 && color == 0 {
+                                color = 1;
+                            }
+                        }
+                        let last = splines[(k + 1) % n];
+                        let mut i = first;
+                        loop {
+                            colors[i] = COLORS[color];
+                            i = next(i);
+                            if i == last {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            start = end;
+        }
+        (colors, corners)
+    }
+
+    /// Returns the portions of the lines that separate the inside of the path
+    /// from the outside. Lines are split at their intersections and the
+    /// pieces that lie in the interior, such as the overlapping portions of
+    /// stroke outlines, are discarded. These would otherwise produce false
+    /// minimums in the interior of the field.
+    fn boundary(&self, colors: &[u8], corners: &[(bool, bool)]) -> Vec<Edge> {
+        const EPSILON: f32 = 1. / 64.;
+        let splits = self.intersections();
+        // Pieces of the lines between intersections along with points just
+        // to either side of their midpoints.
+        let mut pieces = Vec::new();
+        let mut probes = Vec::new();
+        let mut ts = Vec::new();
+        let mut next = 0;
+        for (i, line) in self.lines.iter().enumerate() {
+            ts.clear();
+            ts.push(0.);
+            while next < splits.len() && splits[next].0 == i {
+                ts.push(splits[next].1);
+                next += 1;
+            }
+            ts.push(1.);
+            let dir = line.b - line.a;
+            let len = dir.length();
+            if len == 0. {
+                continue;
+            }
+            let normal = Vector::new(-dir.y, dir.x) * (EPSILON / len);
+            for t in ts.windows(2) {
+                if t[1] - t[0] <= f32::EPSILON {
+                    continue;
+                }
+                let mid = line.a + dir * ((t[0] + t[1]) * 0.5);
+                probes.push(mid + normal);
+                probes.push(mid - normal);
+                pieces.push((i, t[0], t[1]));
+            }
+        }
+        let inside = self.contains_each(&probes);
+        let mut edges = Vec::new();
+        for (k, &(i, t0, t1)) in pieces.iter().enumerate() {
+            let forward = inside[k * 2];
+            if forward == inside[k * 2 + 1] {
+                continue;
+            }
+            let line = &self.lines[i];
+            let dir = line.b - line.a;
+            let a = line.a + dir * t0;
+            let b = line.a + dir * t1;
+            let start_corner = t0 == 0. && corners[i].0;
+            let end_corner = t1 == 1. && corners[i].1;
+            edges.push(if forward {
+                Edge {
+                    line: Line::new(a, b),
+                    color: colors[i],
+                    corners: (start_corner, end_corner),
+                }
+            } else {
+                Edge {
+                    line: Line::new(b, a),
+                    color: colors[i],
+                    corners: (end_corner, start_corner),
+                }
+            });
+        }
+        edges
+    }
+
+    /// Returns the index of each line along with the times at which it
+    /// properly intersects other lines, ordered by index and time. Only
+    /// pairs of lines with overlapping bounding boxes are tested.
+    fn intersections(&self) -> Vec<(usize, f32)> {
+        let lines = &self.lines;
+        let mut order: Vec<usize> = (0..lines.len()).collect();
+        order.sort_unstable_by(|&a, &b| {
+            let (a, b) = (&lines[a], &lines[b]);
+            let (a, b) = (a.a.y.min(a.b.y), b.a.y.min(b.b.y));
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        });
+        let mut splits = Vec::new();
+        for (k, &i) in order.iter().enumerate() {
+            let line = &lines[i];
+            let max_y = line.a.y.max(line.b.y);
+            let (min_x, max_x) = (line.a.x.min(line.b.x), line.a.x.max(line.b.x));
+            for &j in &order[k + 1..] {
+                let other = &lines[j];
+                if other.a.y.min(other.b.y) > max_y {
+                    break;
+                }
+                if other.a.x.max(other.b.x) < min_x || other.a.x.min(other.b.x) > max_x {
+                    continue;
+                }
+                if let Some(t) = intersect(line, other) {
+                    splits.push((i, t));
+                }
+                if let Some(t) = intersect(other, line) {
+                    splits.push((j, t));
+                }
+            }
+        }
+        splits.sort_unstable_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+        });
+        splits
+    }
+
+    /// Returns whether each of the points is inside the path according to the
+    /// fill rule. Points are swept in vertical order such that only the lines
+    /// that span each point are visited.
+    fn contains_each(&self, points: &[Point]) -> Vec<bool> {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_unstable_by(|&a, &b| {
+            points[a]
+                .y
+                .partial_cmp(&points[b].y)
+                .unwrap_or(Ordering::Equal)
+        });
+        let mut lines: Vec<&Line> = self.lines.iter().collect();
+        lines.sort_unstable_by(|a, b| {
+            let (a, b) = (a.a.y.min(a.b.y), b.a.y.min(b.b.y));
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        });
+        let mut active: Vec<&Line> = Vec::new();
+        let mut next = 0;
+        let mut result = alloc::vec![false; points.len()];
+        for i in order {
+            let point = points[i];
+            while let Some(line) = lines.get(next) {
+                if line.a.y.min(line.b.y) > point.y {
+                    break;
+                }
+                active.push(line);
+                next += 1;
+            }
+            active.retain(|line| line.a.y.max(line.b.y) > point.y);
+            let mut winding = 0;
+            for line in &active {
+                if let Some((x, dir)) = crossing(line, point.y) {
+                    if x < point.x {
+                        winding += dir;
+                    }
+                }
+            }
+            result[i] = inside(self.fill, winding);
+        }
+        result
+    }
+}
+
+/// Appends a sequence of lines approximating the curve.
+pub(super) fn flatten(curve: &Curve, lines: &mut Vec<Line>) {
+    let dd = (curve.a - curve.b * 2. + curve.c)
+        .length()
+        .max((curve.b - curve.c * 2. + curve.d).length());
+    let steps = (dd * 0.75 / TOLERANCE).sqrt().ceil().clamp(1., 256.) as usize;
+    let mut prev = curve.a;
+    for i in 1..steps {
+        let p = curve.evaluate(i as f32 / steps as f32);
+        lines.push(Line::new(prev, p));
+        prev = p;
+    }
+    lines.push(Line::new(prev, curve.d));
+}
+
+/// Returns true if the join between two consecutive lines is a corner.
+fn is_corner(a: &Line, b: &Line) -> bool {
+    let da = a.b - a.a;
+    let db = b.b - b.a;
+    let la = da.length();
+    let lb = db.length();
+    if la == 0. || lb == 0. {
+        return false;
+    }
+    let (da, db) = (da * (1. / la), db * (1. / lb));
+    da.dot(db) <= 0. || (da.x * db.y - da.y * db.x).abs() > CORNER_THRESHOLD
+}
+
+/// Returns the horizontal position and direction of the line where it
+/// crosses the specified scanline.
+#[inline(always)]
+pub(super) fn crossing(line: &Line, y: f32) -> Option<(f32, i32)> {
+    let (a, b) = (line.a, line.b);
+    if (a.y <= y) == (b.y <= y) {
+        return None;
+    }
+    let x = a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y);
+    Some((x, if b.y > a.y { -1 } else { 1 }))
+}
+
+/// Returns the time along the first line at which it properly intersects
+/// the second.
+#[inline(always)]
+fn intersect(line: &Line, other: &Line) -> Option<f32> {
+    let d1 = line.b - line.a;
+    let d2 = other.b - other.a;
+    let denom = d1.x * d2.y - d1.y * d2.x;
+    if denom == 0. {
+        return None;
+    }
+    let e = other.a - line.a;
+    let t = (e.x * d2.y - e.y * d2.x) / denom;
+    let u = (e.x * d1.y - e.y * d1.x) / denom;
+    if t > 0. && t < 1. && (0. ..=1.).contains(&u) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Returns the distance from the point to the nearest point on the line.
+#[inline(always)]
+fn distance_to_line(line: &Line, point: Point) -> f32 {
+    let dir = line.b - line.a;
+    let len2 = dir.dot(dir);
+    let t = if len2 == 0. {
+        0.
+    } else {
+        ((point - line.a).dot(dir) / len2).clamp(0., 1.)
+    };
+    (line.a + dir * t - point).length()
+}
+
+/// Returns the signed pseudo-distance from the point to the edge. Beyond
+/// endpoints that are corners, this is the distance to the extension of the
+/// edge.
+#[inline(always)]
+fn pseudo_distance(edge: &Edge, point: Point) -> f32 {
+    let line = &edge.line;
+    let dir = line.b - line.a;
+    let len2 = dir.dot(dir);
+    let t = if len2 == 0. {
+        0.
+    } else {
+        (point - line.a).dot(dir) / len2
+    };
+    let extend = (t < 0. && edge.corners.0) || (t > 1. && edge.corners.1);
+    let t = if extend { t } else { t.clamp(0., 1.) };
+    let d = point - (line.a + dir * t);
+    let distance = d.length();
+    if dir.x * d.y - dir.y * d.x > 0. {
+        distance
+    } else {
+        -distance
+    }
+}
+
+#[inline(always)]
+fn median(a: f32, b: f32, c: f32) -> f32 {
+    a.min(b).max(a.max(b).min(c))
+}
+
+/// Returns true if the winding number is inside according to the fill rule.
+#[inline(always)]
+pub(super) fn inside(fill: Fill, winding: i32) -> bool {
+    match fill {
+        Fill::NonZero => winding != 0,
+        Fill::EvenOdd => winding & 1 != 0,
+        Fill::Positive => winding > 0,
+        Fill::Negative => winding < 0,
+        Fill::AbsGeqTwo => winding.abs() >= 2,
+    }
+}
@@ -1,12 +1,24 @@
 //! Path styles.
 
 /// Describes the visual style of a fill.
+///
+/// The winding number of a point is positive when the path travels around
+/// it counterclockwise in a coordinate system where y points up, or
+/// equivalently, clockwise when rendered with a top left origin.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
 pub enum Fill {
     /// The non-zero fill rule.
     NonZero,
     /// The even-odd fill rule.
     EvenOdd,
+    /// Fills regions with a winding number greater than zero.
+    Positive,
+    /// Fills regions with a winding number less than zero.
+    Negative,
+    /// Fills regions with an absolute winding number of at least two. This is
+    /// useful for detecting overlapping contours.
+    AbsGeqTwo,
 }
 
 /// Defines the connection between two segments of a stroke.
@@ -0,0 +1,259 @@
+//! Tile coverage classification.
+
+use super::command::Command;
+use super::geometry::{Origin, Placement, Transform, Vector};
+use super::mask::Mask;
+use super::path_data::PathData;
+use super::sample::polygons;
+use super::sanitize::Sanitize;
+use super::scratch::Scratch;
+use super::sdf::{crossing, inside, TOLERANCE};
+use super::style::{Fill, Style};
+#[allow(unused)]
+use super::F32Ext;
+
+use crate::lib::Vec;
+use core::cmp::Ordering;
+
+/// Classification of the coverage of a tile.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TileCoverage {
+    /// No pixels in the tile are covered by the path.
+    Empty,
+    /// Some pixels in the tile may be partially covered by the path.
+    Partial,
+    /// All pixels in the tile are fully covered by the path.
+    Full,
+}
+
+/// Builder for classifying the coverage of the tiles of a mask.
+///
+/// The mask is divided into a grid of tiles from its top left corner and
+/// each tile is classified as empty, partially covered or fully covered
+/// without computing the coverage of individual pixels. Tiles along the
+/// right and bottom edges of the mask are smaller when the size of the mask
+/// is not a multiple of the tile size. The style, transform and size of the
+/// mask are determined in the same manner as [`Mask`].
+///
+/// The classification is conservative: a tile is only reported as empty or
+/// full if no edge of the path passes through it, so rendering a partial
+/// tile may still produce uniform coverage.
+///
+/// # Example
+///
+/// ```rust
+/// use zeno::{PathData, TileCoverage, TileMask};
+///
+/// // A 48x16 mask with the first tile covered by the path
+/// let (tiles, placement) = TileMask::new("M 0,0 16,0 16,16 0,16 Z")
+///     .size(48, 16)
+///     .tile_size(16, 16)
+///     .classify();
+///
+/// assert_eq!(placement.width, 48);
+/// assert_eq!(
+///     tiles,
+///     [TileCoverage::Full, TileCoverage::Empty, TileCoverage::Empty]
+/// );
+/// ```
+pub struct TileMask<'a, 's, D> {
+    mask: Mask<'a, 's, D>,
+    tile_size: (u32, u32),
+}
+
+impl<'a, 's, D> TileMask<'a, 's, D>
+where
+    D: PathData,
+{
+    /// Creates a new tile mask builder for the specified path data.
+    pub fn new(data: D) -> Self {
+        Self {
+            mask: Mask::new(data),
+            tile_size: (16, 16),
+        }
+    }
+
+    /// Creates a new tile mask builder for the specified path data and
+    /// scratch memory.
+    pub fn with_scratch(data: D, scratch: &'s mut Scratch) -> Self {
+        Self {
+            mask: Mask::with_scratch(data, scratch),
+            tile_size: (16, 16),
+        }
+    }
+
+    /// Sets the size of each tile in pixels. The default is 16x16.
+    pub fn tile_size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.tile_size = (width.max(1), height.max(1));
+        self
+    }
+
+    /// Sets the style of the path. The default is a non-zero fill.
+    pub fn style(&mut self, style: impl Into<Style<'a>>) -> &mut Self {
+        self.mask.style(style);
+        self
+    }
+
+    /// Sets the transformation matrix of the path.
+    pub fn transform(&mut self, transform: Option<Transform>) -> &mut Self {
+        self.mask.transform(transform);
+        self
+    }
+
+    /// Sets the origin that defines the coordinate system for the mask.
+    pub fn origin(&mut self, origin: Origin) -> &mut Self {
+        self.mask.origin(origin);
+        self
+    }
+
+    /// Sets the offset for the path's rendered bounds. See
+    /// [`Mask::offset`] for details.
+    pub fn offset(&mut self, offset: impl Into<Vector>) -> &mut Self {
+        self.mask.offset(offset);
+        self
+    }
+
+    /// Sets an explicit size for the mask. If left unspecified, the size will
+    /// be computed from the bounding box of the path after applying any
+    /// relevant style, offset and transform.
+    pub fn size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.mask.size(width, height);
+        self
+    }
+
+    /// Restricts classification to a window of the path's coordinate space.
+    /// See [`Mask::clip`] for details.
+    pub fn clip(&mut self, rect: Placement) -> &mut Self {
+        self.mask.clip(rect);
+        self
+    }
+
+    /// Sets the policy for sanitizing the path data. See [`Mask::sanitize`]
+    /// for details.
+    pub fn sanitize(&mut self, policy: Sanitize) -> &mut Self {
+        self.mask.sanitize(policy);
+        self
+    }
+
+    /// Classifies the tiles of the mask and returns the result in row major
+    /// order along with the placement of the mask. Each row contains
+    /// `placement.width` divided by the tile width, rounded up, tiles. Rows
+    /// are ordered from the top of the mask down, or bottom up when the
+    /// origin is bottom left, matching the rows of a rendered mask.
+    pub fn classify(&self) -> (Vec<TileCoverage>, Placement) {
+        self.mask.classify_tiles(self.tile_size)
+    }
+}
+
+/// Classifies the tiles of a window with the specified size. The commands
+/// are translated by the shift into window coordinates.
+pub(super) fn classify(
+    commands: &[Command],
+    shift: Vector,
+    fill: Fill,
+    size: (u32, u32),
+    tile_size: (u32, u32),
+    y_up: bool,
+) -> Vec<TileCoverage> {
+    let (width, height) = size;
+    let (tile_width, tile_height) = tile_size;
+    let columns = width.div_ceil(tile_width) as usize;
+    let rows = height.div_ceil(tile_height) as usize;
+    let mut tiles = alloc::vec![TileCoverage::Empty; columns * rows];
+    if tiles.is_empty() {
+        return tiles;
+    }
+    // Vertical extent of a row of tiles in window coordinates.
+    let band = |row: usize| {
+        let top = row as u32 * tile_height;
+        let bottom = top.saturating_add(tile_height).min(height);
+        if y_up {
+            ((height - bottom) as f32, (height - top) as f32)
+        } else {
+            (top as f32, bottom as f32)
+        }
+    };
+    // Row of tiles containing the vertical window coordinate.
+    let row_at = |y: f32| {
+        let y = if y_up { height as f32 - y } else { y };
+        ((y / tile_height as f32).floor().max(0.) as usize).min(rows - 1)
+    };
+    // Lines approximating curves are padded by the flattening tolerance.
+    let mut lines = Vec::new();
+    polygons(commands, shift, &mut |line, curve| {
+        lines.push((line, if curve { TOLERANCE } else { 0. }));
+    });
+    // Tiles with interiors that are touched by a line are partial.
+    for (line, pad) in &lines {
+        let (a, b) = if line.a.y <= line.b.y {
+            (line.a, line.b)
+        } else {
+            (line.b, line.a)
+        };
+        let (y0, y1) = (a.y - pad, b.y + pad);
+        if !(y1 > 0. && y0 < height as f32) {
+            continue;
+        }
+        let (first_row, last_row) = {
+            let (r0, r1) = (row_at(y0), row_at(y1));
+            (r0.min(r1), r0.max(r1))
+        };
+        for row in first_row..=last_row {
+            let (top, bottom) = band(row);
+            let touches = if y0 == y1 {
+                top < y0 && y0 < bottom
+            } else {
+                y0 < bottom && y1 > top
+            };
+            if !touches {
+                continue;
+            }
+            let (x0, x1) = if a.y == b.y {
+                (a.x.min(b.x), a.x.max(b.x))
+            } else {
+                let x_at = |y: f32| {
+                    let y = y.clamp(a.y, b.y);
+                    a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y)
+                };
+                let (xa, xb) = (x_at(top - pad), x_at(bottom + pad));
+                (xa.min(xb), xa.max(xb))
+            };
+            let (x0, x1) = (x0 - pad, x1 + pad);
+            if !(x1 > 0. && x0 < width as f32) {
+                continue;
+            }
+            let first = (x0 / tile_width as f32).floor().max(0.) as usize;
+            let last = (x1 / tile_width as f32).ceil() as usize;
+            let row = &mut tiles[row * columns..(row + 1) * columns];
+            for tile in row.iter_mut().take(last).skip(first) {
+                *tile = TileCoverage::Partial;
+            }
+        }
+    }
+    // The winding number is constant within the remaining tiles, so they
+    // are classified by their centers.
+    let mut crossings = Vec::new();
+    for row in 0..rows {
+        let (top, bottom) = band(row);
+        let cy = (top + bottom) * 0.5;
+        crossings.clear();
+        crossings.extend(lines.iter().filter_map(|(line, _)| crossing(line, cy)));
+        crossings.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        let mut winding = 0;
+        let mut next = 0;
+        for column in 0..columns {
+            let left = column as u32 * tile_width;
+            let right = left.saturating_add(tile_width).min(width);
+            let cx = (left + right) as f32 * 0.5;
+            while next < crossings.len() && crossings[next].0 < cx {
+                winding += crossings[next].1;
+                next += 1;
+            }
+            let tile = &mut tiles[row * columns + column];
+            if *tile == TileCoverage::Empty && inside(fill, winding) {
+                *tile = TileCoverage::Full;
+            }
+        }
+    }
+    tiles
+}